@@ -3,7 +3,13 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use space_wager::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, StateResponse};
+use space_wager::msg::{
+    AccruedFeesResponse, BalanceResponse, ConfigResponse, CurrentRoundResponse, ExecuteMsg,
+    GameResponse, HouseResponse, InstantiateMsg, JackpotResponse, LeaderboardResponse,
+    PredictionInfo, PredictionsResponse, QueryMsg, ReferralStatsResponse, SolvencyResponse,
+    StateResponse, StatsResponse, StreakResponse, SubscriptionResponse, TimeframesResponse,
+    TournamentEntryResponse, TournamentResponse, UserStatsResponse,
+};
 use space_wager::state::State;
 
 fn main() {
@@ -18,4 +24,22 @@ fn main() {
     export_schema(&schema_for!(State), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(StateResponse), &out_dir);
+    export_schema(&schema_for!(CurrentRoundResponse), &out_dir);
+    export_schema(&schema_for!(StatsResponse), &out_dir);
+    export_schema(&schema_for!(SolvencyResponse), &out_dir);
+    export_schema(&schema_for!(AccruedFeesResponse), &out_dir);
+    export_schema(&schema_for!(HouseResponse), &out_dir);
+    export_schema(&schema_for!(JackpotResponse), &out_dir);
+    export_schema(&schema_for!(StreakResponse), &out_dir);
+    export_schema(&schema_for!(GameResponse), &out_dir);
+    export_schema(&schema_for!(PredictionInfo), &out_dir);
+    export_schema(&schema_for!(PredictionsResponse), &out_dir);
+    export_schema(&schema_for!(UserStatsResponse), &out_dir);
+    export_schema(&schema_for!(ReferralStatsResponse), &out_dir);
+    export_schema(&schema_for!(LeaderboardResponse), &out_dir);
+    export_schema(&schema_for!(TimeframesResponse), &out_dir);
+    export_schema(&schema_for!(SubscriptionResponse), &out_dir);
+    export_schema(&schema_for!(BalanceResponse), &out_dir);
+    export_schema(&schema_for!(TournamentResponse), &out_dir);
+    export_schema(&schema_for!(TournamentEntryResponse), &out_dir);
 }
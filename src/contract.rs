@@ -1,21 +1,40 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Attribute, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env,
-    MessageInfo, Order, Response, StdResult, SubMsg, Uint128, WasmQuery,
+    to_binary, Attribute, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Decimal, Deps, DepsMut,
+    Env, Fraction, MessageInfo, Order, OverflowError, OverflowOperation, Response, StdResult,
+    Storage, SubMsg, Uint128, WasmQuery,
 };
 use cw2::set_contract_version;
 use cw_storage_plus::Bound;
+use sha2::{Digest, Sha256};
 use std::convert::TryInto;
-use std::ops::{Add, Mul, Sub};
-use terraswap::asset::AssetInfo;
-use terraswap::pair::PoolResponse;
 
 use crate::error::ContractError;
-use crate::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, OracleListPriceFeedResponse, OraclePriceFeedQueryMsg, OraclePriceFeedResponse, OraclePriceFeedStateResponse, QueryMsg, StateResponse};
+use crate::leaderboard::{kind_key, update_board};
+use crate::msg::{
+    AccruedFeesResponse, BalanceResponse, ConfigResponse, CurrentRoundResponse, DiscountTier,
+    ExecuteMsg, FeeModel, FeeRecipientMsg, FeeSettings, GameResponse, HouseResponse,
+    InstantiateMsg, JackpotResponse, JackpotWinResponse, LeaderboardKind, LeaderboardPeriod,
+    LeaderboardResponse, OracleListPriceFeedResponse, OraclePriceFeedQueryMsg,
+    OraclePriceFeedResponse, OraclePriceFeedStateResponse, OrderBy, Outcome, PredictionInfo,
+    PredictionStatus, PredictionsResponse, QueryMsg, ReferralStatsResponse, SolvencyResponse,
+    StakingHolderResponse, StakingQueryMsg, StateResponse, StatsResponse, StreakResponse,
+    SubscriptionResponse, TimeframeResponse, TimeframesResponse, TournamentBet,
+    TournamentEntryResponse, TournamentResponse, UserStatsResponse,
+};
 
-use crate::state::{Config, Game, Prediction, State, CONFIG, GAMES, PREDICTIONS, STATE};
+use crate::state::{
+    AutoCompound, Config, FeeRecipient, Game, House, Jackpot, JackpotWin, Prediction, State, Stats,
+    Subscription, Timeframe, Tournament, TournamentEntry, UserStats, AUTO_COMPOUNDS, BALANCES,
+    CONFIG, EPOCH_LEADERBOARDS, GAMES, HOUSE, JACKPOT, LEADERBOARDS, PREDICTIONS, REFERRALS,
+    REFERRERS, STATE, STATS, STREAKS, SUBSCRIPTIONS, TIMEFRAMES, TOURNAMENTS, TOURNAMENT_ENTRIES,
+    USERS, USER_EPOCHS,
+};
 use crate::taxation::deduct_tax;
+use crate::tournament::{
+    prize_shares, settle_entry, MAX_TOURNAMENT_ENTRANTS, MAX_TOURNAMENT_ROUNDS,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:space-wager";
@@ -28,41 +47,72 @@ pub fn instantiate(
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    let state = State { round: 0 };
+    let state = State {
+        round: 0,
+        dust: Uint128::zero(),
+        liabilities: Uint128::zero(),
+        accrued_fees: Uint128::zero(),
+    };
+
+    if msg.epoch_rounds == 0 {
+        return Err(ContractError::InvalidEpochRounds {});
+    }
+    if msg.min_subscription_bet.is_zero() {
+        return Err(ContractError::InvalidMinSubscriptionBet {});
+    }
+    if msg.collector_ratio > Decimal::one() {
+        return Err(ContractError::InvalidFee {});
+    }
 
     let config = Config {
+        admin: deps.api.addr_canonicalize(info.sender.as_str())?,
         pool_address: deps.api.addr_canonicalize(msg.pool_address.as_str())?,
         collector_address: deps.api.addr_canonicalize(msg.collector_address.as_str())?,
         round_time: msg.round_time,
         limit_time: msg.limit_time,
         denom: msg.denom,
         collector_fee: msg.collector_ratio,
+        fee_model: msg.fee_model,
+        min_fee: msg.min_fee,
+        fee_recipients: vec![],
+        staking_address: None,
+        discount_tiers: vec![],
+        referral_share: Decimal::zero(),
+        house_seed: Uint128::zero(),
+        flat_band: Decimal::zero(),
+        bucket_bands: vec![],
+        fixed_multiplier: Decimal::zero(),
+        lock_buffer: 0,
+        reveal_window: 0,
+        reveal_penalty: Decimal::zero(),
+        integrators: vec![],
+        jackpot_share: Decimal::zero(),
+        jackpot_streak: 0,
+        jackpot_min_bet: Uint128::zero(),
+        epoch_rounds: msg.epoch_rounds,
+        epoch_start: env.block.time.plus_seconds(msg.round_time).seconds(),
+        leaderboard_min_rounds: msg.leaderboard_min_rounds,
+        min_subscription_bet: msg.min_subscription_bet,
     };
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     STATE.save(deps.storage, &state)?;
     CONFIG.save(deps.storage, &config)?;
+    STATS.save(deps.storage, &Stats::default())?;
+    HOUSE.save(deps.storage, &House::default())?;
+    JACKPOT.save(deps.storage, &Jackpot::default())?;
 
+    let timeframe = Timeframe {
+        round_time: msg.round_time,
+        limit_time: msg.limit_time,
+        open_round: state.round,
+        locked_round: None,
+    };
+    TIMEFRAMES.save(deps.storage, &DEFAULT_TIMEFRAME.to_be_bytes(), &timeframe)?;
     PREDICTIONS.save(
         deps.storage,
         &state.round.to_be_bytes(),
-        &Prediction {
-            up: Uint128::zero(),
-            down: Uint128::zero(),
-            locked_price: Uint128::zero(),
-            resolved_price: Uint128::zero(),
-            closing_time: env.block.time.plus_seconds(msg.round_time).seconds(),
-            expire_time: env
-                .block
-                .time
-                .plus_seconds(msg.round_time)
-                .plus_seconds(msg.round_time)
-                .plus_seconds(msg.limit_time)
-                .seconds(),
-            success: false,
-            is_up: None,
-            oracle_price_worker: None
-        },
+        &open_prediction(&config, DEFAULT_TIMEFRAME, &timeframe, &env),
     )?;
 
     Ok(Response::new()
@@ -78,105 +128,701 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::MakePrediction { up } => try_make_prediction(deps, env, info, up),
+        ExecuteMsg::MakePrediction {
+            side,
+            referrer,
+            timeframe,
+            amount,
+        } => try_make_prediction(deps, env, info, side, referrer, timeframe, amount),
+        ExecuteMsg::MakeBucketPrediction {
+            bucket,
+            referrer,
+            timeframe,
+        } => try_make_bucket_prediction(deps, env, info, bucket, referrer, timeframe),
+        ExecuteMsg::MakeFixedOddsPrediction {
+            up,
+            referrer,
+            timeframe,
+        } => try_make_fixed_odds_prediction(deps, env, info, up, referrer, timeframe),
+        ExecuteMsg::CommitPrediction {
+            hash,
+            referrer,
+            timeframe,
+        } => try_commit_prediction(deps, env, info, hash, referrer, timeframe),
+        ExecuteMsg::RevealPrediction {
+            up,
+            salt,
+            timeframe,
+        } => try_reveal_prediction(deps, env, info, up, salt, timeframe),
+        ExecuteMsg::Subscribe {
+            side,
+            amount_per_round,
+            rounds,
+            timeframe,
+        } => try_subscribe(deps, env, info, side, amount_per_round, rounds, timeframe),
+        ExecuteMsg::CancelSubscription { timeframe } => {
+            try_cancel_subscription(deps, env, info, timeframe)
+        }
+        ExecuteMsg::SetAutoCompound {
+            enabled,
+            side,
+            timeframe,
+        } => try_set_auto_compound(deps, env, info, enabled, side, timeframe),
+        ExecuteMsg::MakePredictionFor {
+            beneficiary,
+            side,
+            timeframe,
+        } => try_make_prediction_for(deps, env, info, beneficiary, side, timeframe),
+        ExecuteMsg::Deposit {} => try_deposit(deps, env, info),
+        ExecuteMsg::Withdraw { amount } => try_withdraw(deps, env, info, amount),
         ExecuteMsg::ResolveGame { address, round } => {
             try_resolve_game(deps, env, info, address, round)
         }
-        ExecuteMsg::ResolvePrediction {} => try_resolve_prediction(deps, env, info),
+        ExecuteMsg::ResolvePrediction { timeframe } => {
+            try_resolve_prediction(deps, env, info, timeframe)
+        }
+        ExecuteMsg::SweepDust {} => try_sweep_dust(deps, env, info),
+        ExecuteMsg::UpdateConfig {
+            collector_address,
+            collector_ratio,
+            fee_model,
+            min_fee,
+            fee_recipients,
+            staking_address,
+            discount_tiers,
+            referral_share,
+            house_seed,
+            flat_band,
+            bucket_bands,
+            fixed_multiplier,
+            lock_buffer,
+            reveal_window,
+            reveal_penalty,
+            integrators,
+            jackpot_share,
+            jackpot_streak,
+            jackpot_min_bet,
+            min_subscription_bet,
+        } => try_update_config(
+            deps,
+            env,
+            info,
+            collector_address,
+            collector_ratio,
+            fee_model,
+            min_fee,
+            fee_recipients,
+            staking_address,
+            discount_tiers,
+            referral_share,
+            house_seed,
+            flat_band,
+            bucket_bands,
+            fixed_multiplier,
+            lock_buffer,
+            reveal_window,
+            reveal_penalty,
+            integrators,
+            jackpot_share,
+            jackpot_streak,
+            jackpot_min_bet,
+            min_subscription_bet,
+        ),
+        ExecuteMsg::DepositHouse {} => try_deposit_house(deps, env, info),
+        ExecuteMsg::WithdrawHouse { amount } => try_withdraw_house(deps, env, info, amount),
+        ExecuteMsg::ClaimReferralRewards {} => try_claim_referral_rewards(deps, env, info),
+        ExecuteMsg::ClaimJackpot {} => try_claim_jackpot(deps, env, info),
+        ExecuteMsg::DistributeFees {} => try_distribute_fees(deps, env, info),
+        ExecuteMsg::WithdrawFees { amount } => try_withdraw_fees(deps, env, info, amount),
+        ExecuteMsg::AddTimeframe {
+            round_time,
+            limit_time,
+        } => try_add_timeframe(deps, env, info, round_time, limit_time),
+        ExecuteMsg::CreateTournament {
+            timeframe,
+            start_round,
+            end_round,
+            entry_fee,
+            starting_chips,
+            winners,
+        } => try_create_tournament(
+            deps,
+            env,
+            info,
+            timeframe,
+            start_round,
+            end_round,
+            entry_fee,
+            starting_chips,
+            winners,
+        ),
+        ExecuteMsg::EnterTournament { tournament_id } => {
+            try_enter_tournament(deps, env, info, tournament_id)
+        }
+        ExecuteMsg::MakeTournamentPrediction {
+            tournament_id,
+            up,
+            chips,
+        } => try_make_tournament_prediction(deps, env, info, tournament_id, up, chips),
+        ExecuteMsg::SettleTournament { tournament_id } => {
+            try_settle_tournament(deps, env, info, tournament_id)
+        }
+    }
+}
+
+/// Pool of a round a bet goes to
+#[derive(Clone)]
+enum Side {
+    Up,
+    Down,
+    Flat,
+    Bucket(usize),
+    Fixed(bool),
+    /// Hidden side, held out of the pools until revealed
+    Commit(Binary),
+}
+
+impl From<Outcome> for Side {
+    fn from(outcome: Outcome) -> Self {
+        match outcome {
+            Outcome::Up => Side::Up,
+            Outcome::Down => Side::Down,
+            Outcome::Flat => Side::Flat,
+        }
     }
 }
 
+/// Stake entering a round on behalf of a bettor
+struct Bet {
+    bettor: CanonicalAddr,
+    side: Side,
+    amount: Uint128,
+    /// Funds already held for the bettor and counted in the liabilities
+    escrowed: bool,
+    referrer: Option<CanonicalAddr>,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn try_make_prediction(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
+    info: MessageInfo,
+    side: Outcome,
+    referrer: Option<String>,
+    timeframe: Option<u32>,
+    amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    place_bet(deps, env, info, side.into(), referrer, timeframe, amount)
+}
+
+pub fn try_make_bucket_prediction(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    bucket: u32,
+    referrer: Option<String>,
+    timeframe: Option<u32>,
+) -> Result<Response, ContractError> {
+    place_bet(
+        deps,
+        env,
+        info,
+        Side::Bucket(bucket as usize),
+        referrer,
+        timeframe,
+        None,
+    )
+}
+
+pub fn try_make_fixed_odds_prediction(
+    deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     up: bool,
+    referrer: Option<String>,
+    timeframe: Option<u32>,
+) -> Result<Response, ContractError> {
+    place_bet(deps, env, info, Side::Fixed(up), referrer, timeframe, None)
+}
+
+pub fn try_commit_prediction(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    hash: Binary,
+    referrer: Option<String>,
+    timeframe: Option<u32>,
+) -> Result<Response, ContractError> {
+    place_bet(
+        deps,
+        env,
+        info,
+        Side::Commit(hash),
+        referrer,
+        timeframe,
+        None,
+    )
+}
+
+pub fn try_reveal_prediction(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    up: bool,
+    salt: String,
+    timeframe: Option<u32>,
 ) -> Result<Response, ContractError> {
-    let state = STATE.load(deps.storage)?;
     let config = CONFIG.load(deps.storage)?;
-    let raw_sender = deps.api.addr_canonicalize(&info.sender.as_str())?;
-    let sent = match info.funds.len() {
-        0 => Err(ContractError::EmptyFunds {}),
-        1 => {
-            if info.funds[0].denom != config.denom {
-                return Err(ContractError::WrongDenom {});
+    let round = load_timeframe(deps.storage, timeframe.unwrap_or(DEFAULT_TIMEFRAME))?.open_round;
+    let mut prediction = PREDICTIONS.load(deps.storage, &round.to_be_bytes())?;
+    let now = env.block.time.seconds();
+    let commit_buffer = config.lock_buffer + prediction.reveal_window;
+    if betting_open(&prediction, now, commit_buffer)
+        || !betting_open(&prediction, now, config.lock_buffer)
+    {
+        return Err(ContractError::RevealClosed {});
+    }
+
+    let raw_sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let game_key = (raw_sender.as_slice(), &round.to_be_bytes()[..]);
+    let mut game = GAMES
+        .may_load(deps.storage, game_key)?
+        .ok_or(ContractError::InvalidReveal {})?;
+    if game.commitment != Some(commitment_hash(info.sender.as_str(), up, &salt)) {
+        return Err(ContractError::InvalidReveal {});
+    }
+
+    // The stake already paid its entry fee when committed
+    let stake = game.committed;
+    game.commitment = None;
+    game.committed = Uint128::zero();
+    prediction.committed = prediction.committed.checked_sub(stake)?;
+    if up {
+        game.up = game.up.checked_add(stake)?;
+        prediction.up = prediction.up.checked_add(stake)?;
+    } else {
+        game.down = game.down.checked_add(stake)?;
+        prediction.down = prediction.down.checked_add(stake)?;
+    }
+    GAMES.save(deps.storage, game_key, &game)?;
+    PREDICTIONS.save(deps.storage, &round.to_be_bytes(), &prediction)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "reveal_prediction")
+        .add_attribute("entered", if up { "up" } else { "down" })
+        .add_attribute("committed", stake.to_string())
+        .add_attribute("prediction_id", round.to_string()))
+}
+
+/// Commitment of a side, the sha256 of `<sender>:up:<salt>` or `<sender>:down:<salt>`
+fn commitment_hash(sender: &str, up: bool, salt: &str) -> Binary {
+    let direction = if up { "up" } else { "down" };
+    Binary::from(Sha256::digest(format!("{}:{}:{}", sender, direction, salt).as_bytes()).as_slice())
+}
+
+fn place_bet(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    side: Side,
+    referrer: Option<String>,
+    timeframe: Option<u32>,
+    amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let round = load_timeframe(deps.storage, timeframe.unwrap_or(DEFAULT_TIMEFRAME))?.open_round;
+    let raw_sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    // Without funds attached the stake is spent from the internal balance
+    let balance = match amount {
+        Some(amount) if info.funds.is_empty() => {
+            let balance = BALANCES
+                .may_load(deps.storage, raw_sender.as_slice())?
+                .unwrap_or_default();
+            if amount.is_zero() {
+                return Err(ContractError::EmptyFunds {});
             }
-            Ok(info.funds[0].amount)
+            if amount > balance {
+                return Err(ContractError::InsufficientBalance {});
+            }
+            Some(balance.checked_sub(amount)?)
         }
-        _ => Err(ContractError::MultipleDenoms {}),
-    }?;
+        _ => None,
+    };
+    let bet = Bet {
+        bettor: raw_sender.clone(),
+        side,
+        amount: match amount {
+            Some(amount) if balance.is_some() => amount,
+            _ => sent_amount(&info, &config.denom)?,
+        },
+        escrowed: balance.is_some(),
+        referrer: referrer
+            .map(|referrer| deps.api.addr_canonicalize(&referrer))
+            .transpose()?,
+    };
+    let res = enter_round(deps.branch(), &env, round, bet)?;
+    if let Some(balance) = balance {
+        BALANCES.save(deps.storage, raw_sender.as_slice(), &balance)?;
+    }
+    Ok(res)
+}
+
+pub fn try_make_prediction_for(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    beneficiary: String,
+    side: Outcome,
+    timeframe: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let raw_sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if !config.integrators.contains(&raw_sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let round = load_timeframe(deps.storage, timeframe.unwrap_or(DEFAULT_TIMEFRAME))?.open_round;
+    // The game belongs to the beneficiary, the integrator only pays for it
+    let bet = Bet {
+        bettor: deps.api.addr_canonicalize(&beneficiary)?,
+        side: side.into(),
+        amount: sent_amount(&info, &config.denom)?,
+        escrowed: false,
+        referrer: None,
+    };
+    Ok(enter_round(deps, &env, round, bet)?
+        .add_attribute("beneficiary", beneficiary)
+        .add_attribute("integrator", info.sender))
+}
+
+pub fn try_deposit(deps: DepsMut, _env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let amount = sent_amount(&info, &config.denom)?;
+    let raw_sender = deps.api.addr_canonicalize(info.sender.as_str())?;
 
-    match GAMES.may_load(
+    let balance = BALANCES.update(
         deps.storage,
-        (&raw_sender.as_slice(), &state.round.to_be_bytes()),
-    )? {
-        None => {
-            let game = if up {
-                Game {
-                    up: sent,
-                    down: Uint128::zero(),
-                    prize: Uint128::zero(),
-                    resolved: false,
-                }
-            } else {
-                Game {
-                    up: Uint128::zero(),
-                    down: sent,
-                    prize: Uint128::zero(),
-                    resolved: false,
-                }
-            };
-            GAMES.save(
-                deps.storage,
-                (&raw_sender.as_slice(), &state.round.to_be_bytes()),
-                &game,
-            )?;
+        raw_sender.as_slice(),
+        |balance| -> Result<_, ContractError> {
+            Ok(balance.unwrap_or_default().checked_add(amount)?)
+        },
+    )?;
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.liabilities = state.liabilities.checked_add(amount)?;
+        Ok(state)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "deposit")
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("balance", balance.to_string()))
+}
+
+pub fn try_withdraw(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if amount.is_zero() {
+        return Err(ContractError::EmptyFunds {});
+    }
+    let raw_sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let balance = BALANCES
+        .may_load(deps.storage, raw_sender.as_slice())?
+        .unwrap_or_default();
+    if amount > balance {
+        return Err(ContractError::InsufficientBalance {});
+    }
+
+    // Emptying the balance closes it, claims are then sent again
+    let balance = balance.checked_sub(amount)?;
+    if balance.is_zero() {
+        BALANCES.remove(deps.storage, raw_sender.as_slice());
+    } else {
+        BALANCES.save(deps.storage, raw_sender.as_slice(), &balance)?;
+    }
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.liabilities = state.liabilities.checked_sub(amount)?;
+        Ok(state)
+    })?;
+
+    let msg = CosmosMsg::Bank(BankMsg::Send {
+        to_address: info.sender.to_string(),
+        amount: vec![deduct_tax(
+            &deps.querier,
+            Coin {
+                denom: config.denom,
+                amount,
+            },
+        )?],
+    });
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "withdraw")
+        .add_attribute("recipient", info.sender)
+        .add_attribute("amount", amount.to_string()))
+}
+
+fn enter_round(deps: DepsMut, env: &Env, round: u64, bet: Bet) -> Result<Response, ContractError> {
+    let Bet {
+        bettor: raw_sender,
+        side,
+        amount: sent,
+        escrowed,
+        referrer: raw_referrer,
+    } = bet;
+    let mut state = STATE.load(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+    let mut prediction = PREDICTIONS.load(deps.storage, &round.to_be_bytes())?;
+    // Bets landing too close to the lock would know the locked price, and no side is
+    // taken once the reveal window opens so revealed commitments cannot be followed
+    let buffer = config.lock_buffer + prediction.reveal_window;
+    if !betting_open(&prediction, env.block.time.seconds(), buffer) {
+        return Err(ContractError::BettingClosed {});
+    }
+    match side {
+        Side::Flat if prediction.flat_band.is_zero() => return Err(ContractError::FlatDisabled {}),
+        Side::Bucket(bucket) if bucket >= prediction.buckets.len() => {
+            return Err(ContractError::InvalidBucket {})
         }
-        Some(_) => {
-            GAMES.update(
-                deps.storage,
-                (&raw_sender.as_slice(), &state.round.to_be_bytes()),
-                |game| -> Result<Game, ContractError> {
-                    let mut update_game = game.unwrap();
-                    if up {
-                        update_game.up += sent;
-                    } else {
-                        update_game.down += sent;
-                    }
-                    Ok(update_game)
-                },
-            )?;
+        Side::Fixed(_) if prediction.fixed_multiplier.is_zero() => {
+            return Err(ContractError::FixedOddsDisabled {})
+        }
+        Side::Commit(_) if prediction.reveal_window == 0 => {
+            return Err(ContractError::CommitRevealDisabled {})
         }
+        _ => {}
+    }
+    if raw_referrer.as_ref() == Some(&raw_sender) {
+        return Err(ContractError::InvalidReferrer {});
+    }
+
+    // Entry fee is taken from the bet before it reaches the pool
+    let entry_fee = match prediction.fee_settings {
+        Some(FeeSettings {
+            model: FeeModel::Entry,
+            rate,
+            min_fee,
+        }) => compute_fee(rate, min_fee, sent, sent),
+        _ => Uint128::zero(),
     };
+    if !entry_fee.is_zero() && entry_fee >= sent {
+        return Err(ContractError::BetBelowFee {});
+    }
+    let gross = sent;
+    let sent = gross.checked_sub(entry_fee)?;
 
-    PREDICTIONS.update(
-        deps.storage,
-        &state.round.to_be_bytes(),
-        |prediction| -> Result<_, ContractError> {
-            let mut update_prediction = prediction.unwrap();
-            if up {
-                update_prediction.up += sent;
-            } else {
-                update_prediction.down += sent
+    let game_key = (raw_sender.as_slice(), &round.to_be_bytes()[..]);
+    let existing_game = GAMES.may_load(deps.storage, game_key)?;
+    if let Side::Commit(_) = side {
+        if matches!(&existing_game, Some(game) if game.commitment.is_some()) {
+            return Err(ContractError::AlreadyCommitted {});
+        }
+    }
+
+    // The house reserves what it would pay on top of a fixed odds stake
+    let mut exposure = Uint128::zero();
+    if let Side::Fixed(up) = side {
+        let multiplier = prediction.fixed_multiplier;
+        let payout =
+            |stake: Uint128| stake.multiply_ratio(multiplier.numerator(), multiplier.denominator());
+        let stake = existing_game
+            .as_ref()
+            .map(|game| if up { game.fixed_up } else { game.fixed_down })
+            .unwrap_or_default();
+        exposure = payout(stake.checked_add(sent)?)
+            .checked_sub(payout(stake))?
+            .checked_sub(sent)?;
+        if HOUSE.load(deps.storage)?.balance < exposure {
+            return Err(ContractError::ExposureExceeded {});
+        }
+    }
+
+    // Refuse new bets while the balance does not cover what is owed
+    if !escrowed {
+        state.liabilities = state.liabilities.checked_add(gross)?;
+        let balance = deps
+            .querier
+            .query_balance(&env.contract.address, &config.denom)?
+            .amount;
+        if balance < state.liabilities {
+            return Err(ContractError::Insolvent {});
+        }
+    }
+    let jackpot_cut = jackpot_cut(&config, entry_fee);
+    let mut jackpot = JACKPOT.load(deps.storage)?;
+    jackpot.balance = jackpot.balance.checked_add(jackpot_cut)?;
+    state.accrued_fees = state
+        .accrued_fees
+        .checked_add(entry_fee.checked_sub(jackpot_cut)?)?;
+
+    let mut stats = STATS.load(deps.storage)?;
+    stats.total_volume = stats.total_volume.checked_add(gross)?;
+    stats.total_fees = stats.total_fees.checked_add(entry_fee)?;
+    let new_player = USERS
+        .may_load(deps.storage, raw_sender.as_slice())?
+        .is_none();
+    if new_player {
+        stats.unique_players += 1;
+    }
+
+    // The first referrer is kept
+    let referral = match raw_referrer {
+        Some(raw_referrer)
+            if REFERRERS
+                .may_load(deps.storage, raw_sender.as_slice())?
+                .is_none() =>
+        {
+            let mut referral = REFERRALS
+                .may_load(deps.storage, raw_referrer.as_slice())?
+                .unwrap_or_default();
+            referral.referred_users += 1;
+            Some((raw_referrer, referral))
+        }
+        _ => None,
+    };
+
+    let first_bet = existing_game.is_none();
+    let mut game = match existing_game {
+        Some(game) => game,
+        None => {
+            prediction.pending_games += 1;
+            Game {
+                up: Uint128::zero(),
+                down: Uint128::zero(),
+                flat: Uint128::zero(),
+                buckets: vec![Uint128::zero(); prediction.buckets.len()],
+                fixed_up: Uint128::zero(),
+                fixed_down: Uint128::zero(),
+                multiplier: Decimal::zero(),
+                commitment: None,
+                committed: Uint128::zero(),
+                prize: Uint128::zero(),
+                resolved: false,
+                discount: Decimal::zero(),
+                voided: false,
             }
-            Ok(update_prediction)
-        },
-    )?;
+        }
+    };
+    // The winnings fee discount is the lowest held across the bets of the round
+    if let Some(FeeSettings {
+        model: FeeModel::Winnings,
+        ..
+    }) = prediction.fee_settings
+    {
+        let address = deps.api.addr_humanize(&raw_sender)?;
+        let discount = query_discount(deps.as_ref(), &config, address.as_str())?;
+        game.discount = if first_bet {
+            discount
+        } else {
+            game.discount.min(discount)
+        };
+    }
+    match &side {
+        Side::Up => {
+            game.up = game.up.checked_add(sent)?;
+            prediction.up = prediction.up.checked_add(sent)?;
+        }
+        Side::Down => {
+            game.down = game.down.checked_add(sent)?;
+            prediction.down = prediction.down.checked_add(sent)?;
+        }
+        Side::Flat => {
+            game.flat = game.flat.checked_add(sent)?;
+            prediction.flat = prediction.flat.checked_add(sent)?;
+        }
+        Side::Bucket(bucket) => {
+            game.buckets[*bucket] = game.buckets[*bucket].checked_add(sent)?;
+            prediction.buckets[*bucket] = prediction.buckets[*bucket].checked_add(sent)?;
+        }
+        Side::Fixed(true) => {
+            game.fixed_up = game.fixed_up.checked_add(sent)?;
+            game.multiplier = prediction.fixed_multiplier;
+            prediction.fixed_up = prediction.fixed_up.checked_add(sent)?;
+            prediction.fixed_reserved_up = prediction.fixed_reserved_up.checked_add(exposure)?;
+        }
+        Side::Fixed(false) => {
+            game.fixed_down = game.fixed_down.checked_add(sent)?;
+            game.multiplier = prediction.fixed_multiplier;
+            prediction.fixed_down = prediction.fixed_down.checked_add(sent)?;
+            prediction.fixed_reserved_down =
+                prediction.fixed_reserved_down.checked_add(exposure)?;
+        }
+        Side::Commit(hash) => {
+            game.commitment = Some(hash.clone());
+            game.committed = sent;
+            prediction.committed = prediction.committed.checked_add(sent)?;
+        }
+    }
+    let house = if exposure.is_zero() {
+        None
+    } else {
+        let mut house = HOUSE.load(deps.storage)?;
+        house.balance = house.balance.checked_sub(exposure)?;
+        house.in_play = house.in_play.checked_add(exposure)?;
+        Some(house)
+    };
+
+    // Every check passed, a refused bet leaves nothing behind as subscriptions and
+    // auto-compound keep the stake when the bet fails
+    STATE.save(deps.storage, &state)?;
+    if !jackpot_cut.is_zero() {
+        JACKPOT.save(deps.storage, &jackpot)?;
+    }
+    STATS.save(deps.storage, &stats)?;
+    if new_player {
+        USERS.save(deps.storage, raw_sender.as_slice(), &UserStats::default())?;
+    }
+    if let Some((raw_referrer, referral)) = referral {
+        REFERRERS.save(deps.storage, raw_sender.as_slice(), &raw_referrer)?;
+        REFERRALS.save(deps.storage, raw_referrer.as_slice(), &referral)?;
+    }
+    if let Some(house) = house {
+        HOUSE.save(deps.storage, &house)?;
+    }
+    GAMES.save(deps.storage, game_key, &game)?;
+    PREDICTIONS.save(deps.storage, &round.to_be_bytes(), &prediction)?;
 
-    let direction = match up {
-        true => "up",
-        false => "down",
+    let direction = match side {
+        Side::Up => "up".to_string(),
+        Side::Down => "down".to_string(),
+        Side::Flat => "flat".to_string(),
+        Side::Bucket(bucket) => format!("bucket_{}", bucket),
+        Side::Fixed(true) => "fixed_up".to_string(),
+        Side::Fixed(false) => "fixed_down".to_string(),
+        Side::Commit(_) => "commitment".to_string(),
     };
 
-    Ok(Response::new()
+    let mut res = Response::new()
         .add_attribute("action", "make_prediction")
-        .add_attribute("entered", direction.to_string())
+        .add_attribute("entered", direction)
         .add_attribute("committed", sent.to_string())
-        .add_attribute("prediction_id", state.round.to_string()))
+        .add_attribute("prediction_id", round.to_string());
+
+    if !entry_fee.is_zero() {
+        res = res.add_attribute("fee", entry_fee.to_string());
+    }
+    Ok(res)
+}
+
+/// Amount of the single coin sent in the configured denom
+fn sent_amount(info: &MessageInfo, denom: &str) -> Result<Uint128, ContractError> {
+    match info.funds.len() {
+        0 => Err(ContractError::EmptyFunds {}),
+        1 => {
+            if info.funds[0].denom != denom {
+                return Err(ContractError::WrongDenom {});
+            }
+            Ok(info.funds[0].amount)
+        }
+        _ => Err(ContractError::MultipleDenoms {}),
+    }
 }
 
 pub fn try_resolve_game(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     _info: MessageInfo,
     address: String,
@@ -186,11 +832,16 @@ pub fn try_resolve_game(
     let raw_address = deps.api.addr_canonicalize(&address)?;
     let mut prize_amount = Uint128::zero();
     let mut refund_amount = Uint128::zero();
-    //let fee = Uint128::from(1).checked_sub(config.collector_fee);
-    let fee = Decimal::one().sub(config.collector_fee);
+    let mut collector_fee = Uint128::zero();
+    let mut penalty_amount = Uint128::zero();
+    let mut dust_amount = Uint128::zero();
+    let mut last_side = None;
 
+    // Streaks are counted in round order whatever the order of the claims
+    let mut round = round;
+    round.sort_unstable();
     for round_number in round {
-        let prediction = PREDICTIONS.load(deps.storage, &round_number.to_be_bytes())?;
+        let mut prediction = PREDICTIONS.load(deps.storage, &round_number.to_be_bytes())?;
         if prediction.expire_time > env.block.time.seconds() {
             return Err(ContractError::PredictionStillInProgress {});
         }
@@ -200,35 +851,137 @@ pub fn try_resolve_game(
             return Err(ContractError::AlreadyResolved {});
         }
 
-        let mut round_prize = Uint128::zero();
+        last_side = main_side(&game).or(last_side);
+        let side_wagered = game.up.checked_add(game.down)?.checked_add(game.flat)?;
+        let bucket_wagered = sum(&game.buckets)?;
+        let fixed_wagered = game.fixed_up.checked_add(game.fixed_down)?;
+        let wagered = side_wagered
+            .checked_add(bucket_wagered)?
+            .checked_add(fixed_wagered)?
+            .checked_add(game.committed)?;
+        let side_pool = prediction
+            .up
+            .checked_add(prediction.down)?
+            .checked_add(prediction.flat)?;
+        let bucket_pool = sum(&prediction.buckets)?;
+        let pool = side_pool.checked_add(bucket_pool)?;
+        let mut winnings = Uint128::zero();
+        let mut refund = Uint128::zero();
+        let mut round_fee = Uint128::zero();
+        // Fees taken at resolution are not shared with the winners
         if prediction.success {
-            if let Some(is_up) = prediction.is_up {
-                if is_up {
-                    if !game.up.is_zero() {
-                        let up_ratio = Decimal::from_ratio(
-                            prediction.up.checked_add(prediction.down).unwrap(),
-                            prediction.up,
-                        );
-                        let payout = game.up.mul(up_ratio);
-                        round_prize = payout;
-                        prize_amount += payout;
-                    }
-                } else {
-                    if !game.down.is_zero() {
-                        let down_ratio = Decimal::from_ratio(
-                            prediction.up.checked_add(prediction.down).unwrap(),
-                            prediction.down,
-                        );
-                        let payout = game.down.mul(down_ratio);
-                        round_prize = payout;
-                        prize_amount += payout;
+            let (stake, winning_pool) = match (prediction.is_flat, prediction.is_up) {
+                (true, _) => (game.flat, prediction.flat),
+                (false, Some(true)) => (game.up, prediction.up),
+                (false, Some(false)) => (game.down, prediction.down),
+                (false, None) => (Uint128::zero(), Uint128::zero()),
+            };
+            if !stake.is_zero() {
+                winnings =
+                    stake.multiply_ratio(side_pool.checked_sub(prediction.fee)?, winning_pool);
+            }
+        } else {
+            refund = side_wagered;
+        }
+        if !bucket_wagered.is_zero() {
+            match prediction.bucket {
+                Some(bucket) => {
+                    let stake = game.buckets[bucket as usize];
+                    if !stake.is_zero() {
+                        winnings = winnings.checked_add(stake.multiply_ratio(
+                            bucket_pool.checked_sub(prediction.bucket_fee)?,
+                            prediction.buckets[bucket as usize],
+                        ))?;
                     }
                 }
+                None => refund = refund.checked_add(bucket_wagered)?,
             }
-        } else {
-            round_prize = game.down.checked_add(game.up).unwrap();
-            // Refund
-            refund_amount += game.down.checked_add(game.up).unwrap();
+        }
+        if !winnings.is_zero() {
+            if let Some(FeeSettings {
+                model: FeeModel::Winnings,
+                rate,
+                min_fee,
+            }) = prediction.fee_settings
+            {
+                round_fee = compute_fee(rate, min_fee, winnings, winnings);
+                round_fee = round_fee.checked_sub(
+                    round_fee
+                        .multiply_ratio(game.discount.numerator(), game.discount.denominator()),
+                )?;
+            }
+        }
+        // Fixed odds are paid by the house at the quoted multiplier, outside the pools
+        let (fixed_prize, fixed_refund) = match prediction.fixed_outcome {
+            Some(Outcome::Up) => (game.fixed_up, Uint128::zero()),
+            Some(Outcome::Down) => (game.fixed_down, Uint128::zero()),
+            Some(Outcome::Flat) => (Uint128::zero(), Uint128::zero()),
+            None => (Uint128::zero(), fixed_wagered),
+        };
+        let fixed_prize =
+            fixed_prize.multiply_ratio(game.multiplier.numerator(), game.multiplier.denominator());
+
+        // Commitments never revealed are refunded minus the penalty
+        let penalty = game.committed.multiply_ratio(
+            prediction.reveal_penalty.numerator(),
+            prediction.reveal_penalty.denominator(),
+        );
+        let commit_refund = game.committed.checked_sub(penalty)?;
+        penalty_amount = penalty_amount.checked_add(penalty)?;
+
+        let round_prize = winnings.checked_add(refund)?;
+        prize_amount = prize_amount
+            .checked_add(winnings.checked_sub(round_fee)?)?
+            .checked_add(fixed_prize)?;
+        refund_amount = refund_amount
+            .checked_add(refund)?
+            .checked_add(fixed_refund)?
+            .checked_add(commit_refund)?;
+        collector_fee = collector_fee.checked_add(round_fee)?;
+
+        // Payouts and fees of a round can never exceed its pool
+        prediction.claimed = prediction.claimed.checked_add(round_prize)?;
+        if prediction.claimed > pool {
+            return Err(ContractError::PoolExceeded {
+                round: round_number,
+            });
+        }
+        prediction.pending_games = prediction.pending_games.checked_sub(1).ok_or_else(|| {
+            OverflowError::new(OverflowOperation::Sub, prediction.pending_games, 1)
+        })?;
+        // Once every game is resolved the rounding leftover is dust
+        if prediction.pending_games == 0 {
+            dust_amount = dust_amount.checked_add(pool.checked_sub(prediction.claimed)?)?;
+        }
+        PREDICTIONS.save(deps.storage, &round_number.to_be_bytes(), &prediction)?;
+
+        let fixed_payout = fixed_prize
+            .checked_add(fixed_refund)?
+            .checked_add(commit_refund)?;
+        let payout = round_prize
+            .checked_sub(round_fee)?
+            .checked_add(fixed_payout)?;
+        let decided = refund
+            .checked_add(fixed_refund)?
+            .checked_add(game.committed)?
+            != wagered;
+        update_user_stats(
+            deps.storage,
+            &config,
+            &raw_address,
+            prediction.closing_time,
+            wagered,
+            payout,
+            decided,
+        )?;
+        // A round without an outcome leaves the streak as it is
+        if decided {
+            update_streak(
+                deps.storage,
+                &raw_address,
+                round_number,
+                payout > wagered && wagered >= config.jackpot_min_bet,
+            )?;
         }
 
         // Update game as resolved
@@ -238,24 +991,100 @@ pub fn try_resolve_game(
             |game| -> Result<_, ContractError> {
                 let mut update_game = game.unwrap();
                 update_game.resolved = true;
-                update_game.prize = round_prize;
+                update_game.voided = !decided;
+                update_game.prize = round_prize.checked_add(fixed_payout)?;
                 Ok(update_game)
             },
         )?;
     }
 
-    let mut collector_fee = Uint128::zero();
-    let net_prize_amount = if !prize_amount.is_zero() {
-        let net_amount = prize_amount.mul(fee);
-        collector_fee = prize_amount.wrapping_sub(net_amount);
-        net_amount
-    } else {
-        prize_amount
-    };
-    let final_amount = net_prize_amount.add(refund_amount);
+    let final_amount = prize_amount.checked_add(refund_amount)?;
+
+    // The referrer takes a share of the winnings fee, it stays owed until claimed
+    let mut referral_reward = Uint128::zero();
+    if let Some(referrer) = REFERRERS.may_load(deps.storage, raw_address.as_slice())? {
+        referral_reward = collector_fee.multiply_ratio(
+            config.referral_share.numerator(),
+            config.referral_share.denominator(),
+        );
+        if !referral_reward.is_zero() {
+            REFERRALS.update(
+                deps.storage,
+                referrer.as_slice(),
+                |referral| -> Result<_, ContractError> {
+                    let mut referral = referral.unwrap_or_default();
+                    referral.total_rewards = referral.total_rewards.checked_add(referral_reward)?;
+                    referral.pending_rewards =
+                        referral.pending_rewards.checked_add(referral_reward)?;
+                    Ok(referral)
+                },
+            )?;
+        }
+    }
+
+    let auto_compound = AUTO_COMPOUNDS.may_load(deps.storage, raw_address.as_slice())?;
+    let balance = BALANCES.may_load(deps.storage, raw_address.as_slice())?;
+    STATS.update(deps.storage, |mut stats| -> Result<_, ContractError> {
+        stats.total_fees = stats
+            .total_fees
+            .checked_add(collector_fee)?
+            .checked_add(penalty_amount)?;
+        stats.total_paid_out = stats.total_paid_out.checked_add(prize_amount)?;
+        stats.total_refunded = stats.total_refunded.checked_add(refund_amount)?;
+        Ok(stats)
+    })?;
+    let accrued_fee = fund_jackpot(
+        deps.storage,
+        &config,
+        collector_fee
+            .checked_sub(referral_reward)?
+            .checked_add(penalty_amount)?,
+    )?;
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.dust = state.dust.checked_add(dust_amount)?;
+        state.accrued_fees = state.accrued_fees.checked_add(accrued_fee)?;
+        if auto_compound.is_none() && balance.is_none() {
+            state.liabilities = state.liabilities.checked_sub(final_amount)?;
+        }
+        Ok(state)
+    })?;
 
     let mut res = Response::new();
-    if !final_amount.is_zero() {
+    if let Some(mut auto_compound) = auto_compound {
+        // The claim stays owed to the player and is bet again, the balance is left alone
+        auto_compound.last_side = last_side.or(auto_compound.last_side);
+        AUTO_COMPOUNDS.save(deps.storage, raw_address.as_slice(), &auto_compound)?;
+        let unbet = compound(
+            deps.branch(),
+            &env,
+            &raw_address,
+            &auto_compound,
+            final_amount,
+        )?;
+        // A refused bet is held in a balance, one is only opened when something is left
+        if balance.is_some() || !unbet.is_zero() {
+            let balance = balance.unwrap_or_default().checked_add(unbet)?;
+            BALANCES.save(deps.storage, raw_address.as_slice(), &balance)?;
+        }
+        let compounded = final_amount.checked_sub(unbet)?;
+        if !compounded.is_zero() {
+            res.attributes
+                .push(Attribute::new("compounded", compounded.to_string()));
+        }
+        if !unbet.is_zero() {
+            res.attributes
+                .push(Attribute::new("credited", unbet.to_string()));
+        }
+    } else if let Some(balance) = balance {
+        // Players holding a balance are credited instead of paying the transfer tax
+        BALANCES.save(
+            deps.storage,
+            raw_address.as_slice(),
+            &balance.checked_add(final_amount)?,
+        )?;
+        res.attributes
+            .push(Attribute::new("credited", final_amount.to_string()));
+    } else if !final_amount.is_zero() {
         let exec_msg_prize = CosmosMsg::Bank(BankMsg::Send {
             to_address: address.clone(),
             amount: vec![deduct_tax(
@@ -269,56 +1098,769 @@ pub fn try_resolve_game(
         res.messages.push(SubMsg::new(exec_msg_prize));
     }
 
-    if !collector_fee.is_zero() {
-        let exec_msg_collector_fee = CosmosMsg::Bank(BankMsg::Send {
-            to_address: deps
-                .api
-                .addr_humanize(&config.collector_address)?
-                .to_string(),
-            amount: vec![deduct_tax(
-                &deps.querier,
-                Coin {
-                    denom: config.denom,
-                    amount: collector_fee,
-                },
-            )?],
-        });
-
-        res.messages.push(SubMsg::new(exec_msg_collector_fee));
-    }
-
     res.attributes
         .push(Attribute::new("action", "resolve_game"));
     res.attributes.push(Attribute::new("recipient", address));
     Ok(res)
 }
 
-const MAX_LIMIT_QUERY: u32 = 10;
-pub fn try_resolve_prediction(
-    deps: DepsMut,
-    env: Env,
-    _info: MessageInfo,
+/// Highest discount tier reached by the LOTA staked of an address
+fn query_discount(deps: Deps, config: &Config, address: &str) -> StdResult<Decimal> {
+    let staking_address = match &config.staking_address {
+        Some(staking_address) if !config.discount_tiers.is_empty() => staking_address,
+        _ => return Ok(Decimal::zero()),
+    };
+    let query = WasmQuery::Smart {
+        contract_addr: deps.api.addr_humanize(staking_address)?.to_string(),
+        msg: to_binary(&StakingQueryMsg::Holder {
+            address: address.to_string(),
+        })?,
+    };
+    let holder: StakingHolderResponse = deps.querier.query(&query.into())?;
+    Ok(config
+        .discount_tiers
+        .iter()
+        .filter(|tier| holder.balance >= tier.min_stake)
+        .map(|tier| tier.discount)
+        .max()
+        .unwrap_or_else(Decimal::zero))
+}
+
+/// One empty pool per bucket, none when the bucket market is disabled
+fn bucket_pools(bands: &[Decimal]) -> Vec<Uint128> {
+    if bands.is_empty() {
+        vec![]
+    } else {
+        vec![Uint128::zero(); 2 * bands.len() + 1]
+    }
+}
+
+fn sum(amounts: &[Uint128]) -> StdResult<Uint128> {
+    amounts.iter().try_fold(Uint128::zero(), |total, amount| {
+        Ok(total.checked_add(*amount)?)
+    })
+}
+
+/// Bucket of the resolved price, the middle one holds prices within the first band
+fn bucket_index(locked_price: Uint128, resolved_price: Uint128, bands: &[Decimal]) -> usize {
+    let moved = bands
+        .iter()
+        .filter(|band| {
+            let edge = locked_price.multiply_ratio(band.numerator(), band.denominator());
+            if resolved_price >= locked_price {
+                resolved_price - locked_price > edge
+            } else {
+                locked_price - resolved_price > edge
+            }
+        })
+        .count();
+    if resolved_price >= locked_price {
+        bands.len() + moved
+    } else {
+        bands.len() - moved
+    }
+}
+
+/// Fee at the given rate with a flat minimum, never more than the cap
+fn compute_fee(rate: Decimal, min_fee: Uint128, base: Uint128, cap: Uint128) -> Uint128 {
+    base.multiply_ratio(rate.numerator(), rate.denominator())
+        .max(min_fee)
+        .min(cap)
+}
+
+const MAX_BUCKET_BANDS: usize = 10;
+#[allow(clippy::too_many_arguments)]
+pub fn try_update_config(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    collector_address: Option<String>,
+    collector_ratio: Option<Decimal>,
+    fee_model: Option<FeeModel>,
+    min_fee: Option<Uint128>,
+    fee_recipients: Option<Vec<FeeRecipientMsg>>,
+    staking_address: Option<String>,
+    discount_tiers: Option<Vec<DiscountTier>>,
+    referral_share: Option<Decimal>,
+    house_seed: Option<Uint128>,
+    flat_band: Option<Decimal>,
+    bucket_bands: Option<Vec<Decimal>>,
+    fixed_multiplier: Option<Decimal>,
+    lock_buffer: Option<u64>,
+    reveal_window: Option<u64>,
+    reveal_penalty: Option<Decimal>,
+    integrators: Option<Vec<String>>,
+    jackpot_share: Option<Decimal>,
+    jackpot_streak: Option<u32>,
+    jackpot_min_bet: Option<Uint128>,
+    min_subscription_bet: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(collector_address) = collector_address {
+        config.collector_address = deps.api.addr_canonicalize(&collector_address)?;
+    }
+    if let Some(collector_ratio) = collector_ratio {
+        if collector_ratio > Decimal::one() {
+            return Err(ContractError::InvalidFee {});
+        }
+        config.collector_fee = collector_ratio;
+    }
+    if let Some(fee_model) = fee_model {
+        config.fee_model = fee_model;
+    }
+    if let Some(min_fee) = min_fee {
+        config.min_fee = min_fee;
+    }
+    if let Some(fee_recipients) = fee_recipients {
+        // The weights are summed when distributing and must not overflow
+        let total_weight = fee_recipients
+            .iter()
+            .try_fold(0u64, |total, recipient| total.checked_add(recipient.weight));
+        if !fee_recipients.is_empty() && matches!(total_weight, None | Some(0)) {
+            return Err(ContractError::InvalidFeeRecipients {});
+        }
+        config.fee_recipients = fee_recipients
+            .into_iter()
+            .map(|recipient| {
+                Ok(FeeRecipient {
+                    address: deps.api.addr_canonicalize(&recipient.address)?,
+                    weight: recipient.weight,
+                })
+            })
+            .collect::<StdResult<Vec<FeeRecipient>>>()?;
+    }
+    if let Some(staking_address) = staking_address {
+        config.staking_address = Some(deps.api.addr_canonicalize(&staking_address)?);
+    }
+    if let Some(discount_tiers) = discount_tiers {
+        if discount_tiers
+            .iter()
+            .any(|tier| tier.discount > Decimal::one())
+        {
+            return Err(ContractError::InvalidFee {});
+        }
+        config.discount_tiers = discount_tiers;
+    }
+    if let Some(referral_share) = referral_share {
+        if referral_share > Decimal::one() {
+            return Err(ContractError::InvalidFee {});
+        }
+        config.referral_share = referral_share;
+    }
+    if let Some(house_seed) = house_seed {
+        config.house_seed = house_seed;
+    }
+    if let Some(flat_band) = flat_band {
+        if flat_band >= Decimal::one() {
+            return Err(ContractError::InvalidFlatBand {});
+        }
+        config.flat_band = flat_band;
+    }
+    if let Some(bucket_bands) = bucket_bands {
+        // Bands must grow strictly from a positive first one
+        if bucket_bands.len() > MAX_BUCKET_BANDS
+            || bucket_bands.first() == Some(&Decimal::zero())
+            || bucket_bands.windows(2).any(|pair| pair[0] >= pair[1])
+        {
+            return Err(ContractError::InvalidBuckets {});
+        }
+        config.bucket_bands = bucket_bands;
+    }
+    if let Some(fixed_multiplier) = fixed_multiplier {
+        if !fixed_multiplier.is_zero() && fixed_multiplier <= Decimal::one() {
+            return Err(ContractError::InvalidMultiplier {});
+        }
+        config.fixed_multiplier = fixed_multiplier;
+    }
+    if let Some(reveal_penalty) = reveal_penalty {
+        if reveal_penalty > Decimal::one() {
+            return Err(ContractError::InvalidFee {});
+        }
+        config.reveal_penalty = reveal_penalty;
+    }
+    if let Some(integrators) = integrators {
+        config.integrators = integrators
+            .iter()
+            .map(|integrator| deps.api.addr_canonicalize(integrator))
+            .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+    }
+    if let Some(jackpot_share) = jackpot_share {
+        if jackpot_share > Decimal::one() {
+            return Err(ContractError::InvalidFee {});
+        }
+        config.jackpot_share = jackpot_share;
+    }
+    if let Some(jackpot_streak) = jackpot_streak {
+        config.jackpot_streak = jackpot_streak;
+    }
+    if let Some(jackpot_min_bet) = jackpot_min_bet {
+        config.jackpot_min_bet = jackpot_min_bet;
+    }
+    if let Some(min_subscription_bet) = min_subscription_bet {
+        if min_subscription_bet.is_zero() {
+            return Err(ContractError::InvalidMinSubscriptionBet {});
+        }
+        config.min_subscription_bet = min_subscription_bet;
+    }
+    if lock_buffer.is_some() || reveal_window.is_some() {
+        config.lock_buffer = lock_buffer.unwrap_or(config.lock_buffer);
+        config.reveal_window = reveal_window.unwrap_or(config.reveal_window);
+        let closed_time = config.lock_buffer.saturating_add(config.reveal_window);
+        for pair in TIMEFRAMES.range(deps.storage, None, None, Order::Ascending) {
+            let (_, timeframe) = pair?;
+            if closed_time >= timeframe.round_time {
+                return Err(ContractError::InvalidLockBuffer {});
+            }
+        }
+    }
+    // Referrers are paid from the winnings fee, other models would never reward them
+    if !config.referral_share.is_zero() && config.fee_model != FeeModel::Winnings {
+        return Err(ContractError::ReferralWithoutWinningsFee {});
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
+pub fn try_distribute_fees(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+    if state.accrued_fees.is_zero() {
+        return Err(ContractError::EmptyFunds {});
+    }
+
+    let res = Response::new().add_attribute("action", "distribute_fees");
+    let (res, distributed) = pay_fee_recipients(deps.as_ref(), &config, res, state.accrued_fees)?;
+
+    // Rounding leftover stays accrued for the next distribution
+    state.accrued_fees = state.accrued_fees.checked_sub(distributed)?;
+    state.liabilities = state.liabilities.checked_sub(distributed)?;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(res.add_attribute("distributed", distributed.to_string()))
+}
+
+/// Split an amount of fees between the fee recipients by weight, returns the amount sent
+fn pay_fee_recipients(
+    deps: Deps,
+    config: &Config,
+    mut res: Response,
+    fees: Uint128,
+) -> Result<(Response, Uint128), ContractError> {
+    // Without recipients the collector receives everything
+    let recipients = if config.fee_recipients.is_empty() {
+        vec![FeeRecipient {
+            address: config.collector_address.clone(),
+            weight: 1,
+        }]
+    } else {
+        config.fee_recipients.clone()
+    };
+    let total_weight: u64 = recipients.iter().map(|recipient| recipient.weight).sum();
+
+    let mut distributed = Uint128::zero();
+    for recipient in recipients {
+        let amount = fees.multiply_ratio(recipient.weight, total_weight);
+        if amount.is_zero() {
+            continue;
+        }
+        distributed = distributed.checked_add(amount)?;
+        let address = deps.api.addr_humanize(&recipient.address)?;
+        res = res
+            .add_message(BankMsg::Send {
+                to_address: address.to_string(),
+                amount: vec![deduct_tax(
+                    &deps.querier,
+                    Coin {
+                        denom: config.denom.clone(),
+                        amount,
+                    },
+                )?],
+            })
+            .add_attribute(address.to_string(), amount.to_string());
+    }
+    Ok((res, distributed))
+}
+
+pub fn try_sweep_dust(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if state.dust.is_zero() {
+        return Err(ContractError::EmptyFunds {});
+    }
+
+    let amount = state.dust;
+    state.dust = Uint128::zero();
+    state.liabilities = state.liabilities.checked_sub(amount)?;
+    STATE.save(deps.storage, &state)?;
+
+    let collector = deps.api.addr_humanize(&config.collector_address)?;
+    let msg = CosmosMsg::Bank(BankMsg::Send {
+        to_address: collector.to_string(),
+        amount: vec![deduct_tax(
+            &deps.querier,
+            Coin {
+                denom: config.denom,
+                amount,
+            },
+        )?],
+    });
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "sweep_dust")
+        .add_attribute("recipient", collector)
+        .add_attribute("amount", amount.to_string()))
+}
+
+pub fn try_withdraw_fees(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender != config.admin && sender != config.collector_address {
+        return Err(ContractError::Unauthorized {});
+    }
+    if amount.is_zero() {
+        return Err(ContractError::EmptyFunds {});
+    }
+    if amount > state.accrued_fees {
+        return Err(ContractError::InsufficientFees {});
+    }
+
+    // Withdrawals follow the same weighted split as distributions
+    let res = Response::new().add_attribute("action", "withdraw_fees");
+    let (res, withdrawn) = pay_fee_recipients(deps.as_ref(), &config, res, amount)?;
+    state.accrued_fees = state.accrued_fees.checked_sub(withdrawn)?;
+    state.liabilities = state.liabilities.checked_sub(withdrawn)?;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(res.add_attribute("amount", withdrawn.to_string()))
+}
+
+pub fn try_claim_referral_rewards(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let raw_sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut referral = REFERRALS
+        .may_load(deps.storage, raw_sender.as_slice())?
+        .unwrap_or_default();
+    if referral.pending_rewards.is_zero() {
+        return Err(ContractError::EmptyFunds {});
+    }
+
+    let amount = referral.pending_rewards;
+    referral.pending_rewards = Uint128::zero();
+    REFERRALS.save(deps.storage, raw_sender.as_slice(), &referral)?;
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.liabilities = state.liabilities.checked_sub(amount)?;
+        Ok(state)
+    })?;
+
+    let msg = CosmosMsg::Bank(BankMsg::Send {
+        to_address: info.sender.to_string(),
+        amount: vec![deduct_tax(
+            &deps.querier,
+            Coin {
+                denom: config.denom,
+                amount,
+            },
+        )?],
+    });
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "claim_referral_rewards")
+        .add_attribute("recipient", info.sender)
+        .add_attribute("amount", amount.to_string()))
+}
+
+pub fn try_deposit_house(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    let amount = sent_amount(&info, &config.denom)?;
+
+    HOUSE.update(deps.storage, |mut house| -> Result<_, ContractError> {
+        house.balance = house.balance.checked_add(amount)?;
+        house.total_deposited = house.total_deposited.checked_add(amount)?;
+        Ok(house)
+    })?;
+    // House liquidity is owed back to the admin
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.liabilities = state.liabilities.checked_add(amount)?;
+        Ok(state)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "deposit_house")
+        .add_attribute("amount", amount.to_string()))
+}
+
+pub fn try_withdraw_house(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if amount.is_zero() {
+        return Err(ContractError::EmptyFunds {});
+    }
+    let mut house = HOUSE.load(deps.storage)?;
+    if amount > house.balance {
+        return Err(ContractError::InsufficientHouseBalance {});
+    }
+
+    house.balance = house.balance.checked_sub(amount)?;
+    house.total_withdrawn = house.total_withdrawn.checked_add(amount)?;
+    HOUSE.save(deps.storage, &house)?;
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.liabilities = state.liabilities.checked_sub(amount)?;
+        Ok(state)
+    })?;
+
+    let msg = CosmosMsg::Bank(BankMsg::Send {
+        to_address: info.sender.to_string(),
+        amount: vec![deduct_tax(
+            &deps.querier,
+            Coin {
+                denom: config.denom,
+                amount,
+            },
+        )?],
+    });
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "withdraw_house")
+        .add_attribute("recipient", info.sender)
+        .add_attribute("amount", amount.to_string()))
+}
+
+/// Side holding most of the stake of a game on the up, down and flat pools
+fn main_side(game: &Game) -> Option<Outcome> {
+    [
+        (Outcome::Up, game.up),
+        (Outcome::Down, game.down),
+        (Outcome::Flat, game.flat),
+    ]
+    .iter()
+    .filter(|(_, stake)| !stake.is_zero())
+    .max_by_key(|(_, stake)| *stake)
+    .map(|(side, _)| *side)
+}
+
+/// Bet the balance in the open round of the timeframe, a refused bet keeps it for later as
+/// `enter_round` writes nothing before every check passed
+fn compound(
+    deps: DepsMut,
+    env: &Env,
+    address: &CanonicalAddr,
+    auto_compound: &AutoCompound,
+    balance: Uint128,
+) -> Result<Uint128, ContractError> {
+    let side = match auto_compound.side.or(auto_compound.last_side) {
+        Some(side) if !balance.is_zero() => side,
+        _ => return Ok(balance),
+    };
+    let round = load_timeframe(deps.storage, auto_compound.timeframe)?.open_round;
+    let bet = Bet {
+        bettor: address.clone(),
+        side: side.into(),
+        amount: balance,
+        escrowed: true,
+        referrer: None,
+    };
+    Ok(match enter_round(deps, env, round, bet) {
+        Ok(_) => Uint128::zero(),
+        Err(_) => balance,
+    })
+}
+
+pub fn try_set_auto_compound(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    enabled: bool,
+    side: Option<Outcome>,
+    timeframe: Option<u32>,
+) -> Result<Response, ContractError> {
+    let raw_sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let res = Response::new().add_attribute("action", "set_auto_compound");
+
+    // Only claims are compounded, the balance stays available to withdraw
+    if !enabled {
+        AUTO_COMPOUNDS.remove(deps.storage, raw_sender.as_slice());
+        return Ok(res);
+    }
+
+    let timeframe = timeframe.unwrap_or(DEFAULT_TIMEFRAME);
+    load_timeframe(deps.storage, timeframe)?;
+    let last_side = AUTO_COMPOUNDS
+        .may_load(deps.storage, raw_sender.as_slice())?
+        .and_then(|auto_compound| auto_compound.last_side);
+    let auto_compound = AutoCompound {
+        timeframe,
+        side,
+        last_side,
+    };
+    AUTO_COMPOUNDS.save(deps.storage, raw_sender.as_slice(), &auto_compound)?;
+
+    Ok(res.add_attribute("timeframe", timeframe.to_string()))
+}
+
+/// Jackpot share of a fee
+fn jackpot_cut(config: &Config, fee: Uint128) -> Uint128 {
+    fee.multiply_ratio(
+        config.jackpot_share.numerator(),
+        config.jackpot_share.denominator(),
+    )
+}
+
+/// Set aside the jackpot share of a fee and return what is left to accrue
+fn fund_jackpot(storage: &mut dyn Storage, config: &Config, fee: Uint128) -> StdResult<Uint128> {
+    let share = jackpot_cut(config, fee);
+    if !share.is_zero() {
+        JACKPOT.update(storage, |mut jackpot| -> StdResult<_> {
+            jackpot.balance = jackpot.balance.checked_add(share)?;
+            Ok(jackpot)
+        })?;
+    }
+    Ok(fee.checked_sub(share)?)
+}
+
+/// Only a net win of at least the minimum stake extends the streak, and only when every game
+/// played since the last win was resolved without an outcome
+fn update_streak(
+    storage: &mut dyn Storage,
+    raw_address: &CanonicalAddr,
+    round: u64,
+    won: bool,
+) -> StdResult<()> {
+    let mut streak = STREAKS
+        .may_load(storage, raw_address.as_slice())?
+        .unwrap_or_default();
+    if !won {
+        streak.count = 0;
+    } else {
+        let mut consecutive = streak.count > 0 && round > streak.last_round;
+        if consecutive {
+            let between = GAMES.prefix(raw_address.as_slice()).range(
+                storage,
+                Some(Bound::Exclusive(streak.last_round.to_be_bytes().to_vec())),
+                Some(Bound::Exclusive(round.to_be_bytes().to_vec())),
+                Order::Ascending,
+            );
+            for item in between {
+                let (_, game) = item?;
+                if !game.voided {
+                    consecutive = false;
+                    break;
+                }
+            }
+        }
+        streak.count = if consecutive { streak.count + 1 } else { 1 };
+        streak.last_round = round;
+    }
+    STREAKS.save(storage, raw_address.as_slice(), &streak)
+}
+
+const JACKPOT_WINNERS: usize = 10;
+pub fn try_claim_jackpot(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let raw_sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut streak = STREAKS
+        .may_load(deps.storage, raw_sender.as_slice())?
+        .unwrap_or_default();
+    if config.jackpot_streak == 0 || streak.count < config.jackpot_streak {
+        return Err(ContractError::JackpotNotWon {});
+    }
+    let mut jackpot = JACKPOT.load(deps.storage)?;
+    let amount = jackpot.balance;
+    if amount.is_zero() {
+        return Err(ContractError::EmptyJackpot {});
+    }
+
+    jackpot.balance = Uint128::zero();
+    jackpot.total_paid = jackpot.total_paid.checked_add(amount)?;
+    jackpot.winners.insert(
+        0,
+        JackpotWin {
+            address: raw_sender.clone(),
+            amount,
+            streak: streak.count,
+        },
+    );
+    jackpot.winners.truncate(JACKPOT_WINNERS);
+    JACKPOT.save(deps.storage, &jackpot)?;
+    // The streak is spent on the claim
+    streak.count = 0;
+    STREAKS.save(deps.storage, raw_sender.as_slice(), &streak)?;
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.liabilities = state.liabilities.checked_sub(amount)?;
+        Ok(state)
+    })?;
+
+    let msg = CosmosMsg::Bank(BankMsg::Send {
+        to_address: info.sender.to_string(),
+        amount: vec![deduct_tax(
+            &deps.querier,
+            Coin {
+                denom: config.denom,
+                amount,
+            },
+        )?],
+    });
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "claim_jackpot")
+        .add_attribute("recipient", info.sender)
+        .add_attribute("amount", amount.to_string()))
+}
+
+/// Leaderboard epoch of a time, epochs last `epoch_rounds` rounds of the default timeframe
+fn epoch_at(config: &Config, time: u64) -> u64 {
+    let length = config.epoch_rounds.saturating_mul(config.round_time).max(1);
+    time.saturating_sub(config.epoch_start) / length
+}
+
+fn update_user_stats(
+    storage: &mut dyn Storage,
+    config: &Config,
+    raw_address: &CanonicalAddr,
+    closing_time: u64,
+    wagered: Uint128,
+    payout: Uint128,
+    success: bool,
+) -> StdResult<()> {
+    let record = |stats: Option<UserStats>| -> StdResult<UserStats> {
+        let mut stats = stats.unwrap_or_default();
+        stats.volume = stats.volume.checked_add(wagered)?;
+        stats.payout = stats.payout.checked_add(payout)?;
+        if !success {
+            stats.rounds_refunded += 1;
+        } else {
+            stats.rounds_played += 1;
+            if !payout.is_zero() {
+                stats.rounds_won += 1;
+            }
+        }
+        Ok(stats)
+    };
+    let epoch = epoch_at(config, closing_time).to_be_bytes();
+    let stats = USERS.update(storage, raw_address.as_slice(), record)?;
+    let epoch_stats = USER_EPOCHS.update(storage, (raw_address.as_slice(), &epoch), record)?;
+
+    for kind in [
+        LeaderboardKind::NetWinnings,
+        LeaderboardKind::WinRate,
+        LeaderboardKind::Volume,
+    ] {
+        let key = kind_key(kind);
+        let mut board = LEADERBOARDS.may_load(storage, key)?.unwrap_or_default();
+        update_board(
+            &mut board,
+            kind,
+            raw_address,
+            &stats,
+            config.leaderboard_min_rounds,
+        );
+        LEADERBOARDS.save(storage, key, &board)?;
+
+        let mut epoch_board = EPOCH_LEADERBOARDS
+            .may_load(storage, (key, &epoch))?
+            .unwrap_or_default();
+        update_board(
+            &mut epoch_board,
+            kind,
+            raw_address,
+            &epoch_stats,
+            config.leaderboard_min_rounds,
+        );
+        EPOCH_LEADERBOARDS.save(storage, (key, &epoch), &epoch_board)?;
+    }
+    Ok(())
+}
+
+const MAX_LIMIT_QUERY: u32 = 10;
+/// Timeframe created at instantiation, used when none is given
+const DEFAULT_TIMEFRAME: u32 = 0;
+pub fn try_resolve_prediction(
+    mut deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    timeframe: Option<u32>,
 ) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
     let config = CONFIG.load(deps.storage)?;
-    let prediction_now = PREDICTIONS.load(deps.storage, &state.round.to_be_bytes())?;
+    let timeframe_id = timeframe.unwrap_or(DEFAULT_TIMEFRAME);
+    let mut timeframe = load_timeframe(deps.storage, timeframe_id)?;
+    let prediction_now = PREDICTIONS.load(deps.storage, &timeframe.open_round.to_be_bytes())?;
 
     // Check if the round is open to be resolved
     if prediction_now.closing_time > env.block.time.seconds() {
         return Err(ContractError::PredictionStillInProgress {});
     }
     let query_state_price_feed = OraclePriceFeedQueryMsg::State {};
-    let query_wasm_state = WasmQuery::Smart { contract_addr: deps.api.addr_humanize(&config.pool_address)?.to_string(), msg: to_binary(&query_state_price_feed)?};
-    let state_info: OraclePriceFeedStateResponse =  deps.querier.query(&query_wasm_state.into())?;
+    let query_wasm_state = WasmQuery::Smart {
+        contract_addr: deps.api.addr_humanize(&config.pool_address)?.to_string(),
+        msg: to_binary(&query_state_price_feed)?,
+    };
+    let state_info: OraclePriceFeedStateResponse = deps.querier.query(&query_wasm_state.into())?;
 
-    let query_list_price_feed = OraclePriceFeedQueryMsg::GetListPriceFeed { start_after: Some(state_info.round), limit: Some(MAX_LIMIT_QUERY) };
-    let query_wasm_list_price_feed = WasmQuery::Smart { contract_addr: deps.api.addr_humanize(&config.pool_address)?.to_string(), msg: to_binary(&query_list_price_feed)?};
-    let list_price_feed_info: OracleListPriceFeedResponse = deps.querier.query(&query_wasm_list_price_feed.into())?;
+    let query_list_price_feed = OraclePriceFeedQueryMsg::GetListPriceFeed {
+        start_after: Some(state_info.round),
+        limit: Some(MAX_LIMIT_QUERY),
+    };
+    let query_wasm_list_price_feed = WasmQuery::Smart {
+        contract_addr: deps.api.addr_humanize(&config.pool_address)?.to_string(),
+        msg: to_binary(&query_list_price_feed)?,
+    };
+    let list_price_feed_info: OracleListPriceFeedResponse =
+        deps.querier.query(&query_wasm_list_price_feed.into())?;
 
-    let mut data_price_feed = list_price_feed_info.list.iter().filter(
-        |&price_feed| price_feed.timestamp > env.block.time.seconds()
-    ).collect::<Vec<OraclePriceFeedResponse>>();
-    data_price_feed.sort_by(|a, b| a.price.cmp(&b.price));
+    let mut data_price_feed = list_price_feed_info
+        .list
+        .into_iter()
+        .filter(|price_feed| price_feed.timestamp > env.block.time.seconds())
+        .collect::<Vec<OraclePriceFeedResponse>>();
+    data_price_feed.sort_by_key(|a| a.price);
 
     let data_price_feed_average = data_price_feed[5].clone();
 
@@ -355,174 +1897,1317 @@ pub fn try_resolve_prediction(
 
     let mut res = Response::new();
     // Resolve the past prediction
-    if state.round != 0 {
-        let prediction = PREDICTIONS.load(deps.storage, &(state.round - 1).to_be_bytes())?;
-        // Check if not expired and prediction up and down are not zero
-        let is_success = env.block.time.seconds() < prediction.expire_time
-            && !prediction.up.is_zero()
-            && !prediction.down.is_zero()
-            && prediction.locked_price != predicted_price;
-
+    if let Some(locked_round) = timeframe.locked_round {
+        let prediction = PREDICTIONS.load(deps.storage, &locked_round.to_be_bytes())?;
+        // A price staying within the band around the locked price resolves flat
+        let band = prediction.locked_price.multiply_ratio(
+            prediction.flat_band.numerator(),
+            prediction.flat_band.denominator(),
+        );
+        let is_flat = !prediction.flat_band.is_zero()
+            && predicted_price <= prediction.locked_price.checked_add(band)?
+            && predicted_price.checked_add(band)? >= prediction.locked_price;
         let is_up = predicted_price > prediction.locked_price;
-        // Update the current prediction
-        PREDICTIONS.update(
-            deps.storage,
-            &(state.round - 1).to_be_bytes(),
-            |prediction| -> Result<_, ContractError> {
-                let mut update_prediction = prediction.unwrap();
-                if is_success {
-                    update_prediction.is_up = Some(is_up);
-                    update_prediction.resolved_price = predicted_price;
-                }
-                update_prediction.success = is_success;
-                Ok(update_prediction)
-            },
-        )?;
 
-        let direction = match is_up {
-            true => "up",
-            false => "down",
+        let pool = prediction
+            .up
+            .checked_add(prediction.down)?
+            .checked_add(prediction.flat)?;
+        let (winning_pool, house_winning) = if is_flat {
+            (prediction.flat, Uint128::zero())
+        } else if is_up {
+            (prediction.up, prediction.house_up)
+        } else {
+            (prediction.down, prediction.house_down)
         };
-        res.attributes.push(Attribute::new(
-            "prediction_id",
-            (state.round - 1).to_string(),
-        ));
+        let losing_pool = pool.checked_sub(winning_pool)?;
 
-        res.attributes.push(Attribute::new(
-            "locked_price",
-            prediction.locked_price.to_string(),
-        ));
-        res.attributes
-            .push(Attribute::new("is_success", is_success.to_string()));
+        // Check if not expired, the price moved or stayed flat and there are winners and losers
+        let is_success = env.block.time.seconds() < prediction.expire_time
+            && (is_flat || prediction.locked_price != predicted_price)
+            && !winning_pool.is_zero()
+            && !losing_pool.is_zero();
 
+        // Pool fees are taken once the winning side is known
+        let mut fee = Uint128::zero();
         if is_success {
-            res.attributes
-                .push(Attribute::new("resolved", direction.to_string()));
-            res.attributes.push(Attribute::new(
-                "resolved_price",
-                predicted_price.to_string(),
-            ));
+            if let Some(settings) = &prediction.fee_settings {
+                fee = match settings.model {
+                    FeeModel::TotalPool => {
+                        compute_fee(settings.rate, settings.min_fee, pool, losing_pool)
+                    }
+                    FeeModel::LosingPool => {
+                        compute_fee(settings.rate, settings.min_fee, losing_pool, losing_pool)
+                    }
+                    FeeModel::Winnings | FeeModel::Entry => Uint128::zero(),
+                };
+            }
         }
-    }
-    res.attributes
-        .push(Attribute::new("action", "resolve_prediction"));
 
-    // Update locked price of the current prediction
-    PREDICTIONS.update(
-        deps.storage,
-        &state.round.to_be_bytes(),
-        |prediction| -> Result<_, ContractError> {
-            let mut update_prediction = prediction.unwrap();
+        // The house takes its share right away, its seed is refunded on a voided round
+        let house_stake = prediction.house_up.checked_add(prediction.house_down)?;
+        let mut house_return = Uint128::zero();
+        if !house_stake.is_zero() {
+            house_return = if is_success {
+                house_winning.multiply_ratio(pool.checked_sub(fee)?, winning_pool)
+            } else {
+                house_stake
+            };
+            HOUSE.update(deps.storage, |mut house| -> Result<_, ContractError> {
+                house.balance = house.balance.checked_add(house_return)?;
+                house.in_play = house.in_play.checked_sub(house_stake)?;
+                if house_return > house_stake {
+                    house.total_won = house
+                        .total_won
+                        .checked_add(house_return.checked_sub(house_stake)?)?;
+                } else {
+                    house.total_lost = house
+                        .total_lost
+                        .checked_add(house_stake.checked_sub(house_return)?)?;
+                }
+                Ok(house)
+            })?;
+        }
+
+        // Fixed odds settle on the price alone, the house keeps the losing stakes
+        let fixed_outcome = if env.block.time.seconds() >= prediction.expire_time
+            || (!is_flat && prediction.locked_price == predicted_price)
+        {
+            None
+        } else if is_flat {
+            Some(Outcome::Flat)
+        } else if is_up {
+            Some(Outcome::Up)
+        } else {
+            Some(Outcome::Down)
+        };
+        let fixed_reserved = prediction
+            .fixed_reserved_up
+            .checked_add(prediction.fixed_reserved_down)?;
+        if !prediction.fixed_up.is_zero() || !prediction.fixed_down.is_zero() {
+            let (kept, paid) = match fixed_outcome {
+                Some(Outcome::Up) => (prediction.fixed_down, prediction.fixed_reserved_up),
+                Some(Outcome::Down) => (prediction.fixed_up, prediction.fixed_reserved_down),
+                Some(Outcome::Flat) => (
+                    prediction.fixed_up.checked_add(prediction.fixed_down)?,
+                    Uint128::zero(),
+                ),
+                None => (Uint128::zero(), Uint128::zero()),
+            };
+            HOUSE.update(deps.storage, |mut house| -> Result<_, ContractError> {
+                house.balance = house
+                    .balance
+                    .checked_add(fixed_reserved.checked_sub(paid)?)?
+                    .checked_add(kept)?;
+                house.in_play = house.in_play.checked_sub(fixed_reserved)?;
+                house.total_won = house.total_won.checked_add(kept)?;
+                house.total_lost = house.total_lost.checked_add(paid)?;
+                Ok(house)
+            })?;
+        }
+
+        // The bucket market resolves on its own pools
+        let mut bucket = None;
+        let mut bucket_fee = Uint128::zero();
+        if !prediction.buckets.is_empty() {
+            let index = bucket_index(
+                prediction.locked_price,
+                predicted_price,
+                &prediction.bucket_bands,
+            );
+            let bucket_pool = sum(&prediction.buckets)?;
+            let winning_pool = prediction.buckets[index];
+            let losing_pool = bucket_pool.checked_sub(winning_pool)?;
+            if env.block.time.seconds() < prediction.expire_time
+                && !winning_pool.is_zero()
+                && !losing_pool.is_zero()
+            {
+                bucket = Some(index as u32);
+                if let Some(settings) = &prediction.fee_settings {
+                    bucket_fee = match settings.model {
+                        FeeModel::TotalPool => {
+                            compute_fee(settings.rate, settings.min_fee, bucket_pool, losing_pool)
+                        }
+                        FeeModel::LosingPool => {
+                            compute_fee(settings.rate, settings.min_fee, losing_pool, losing_pool)
+                        }
+                        FeeModel::Winnings | FeeModel::Entry => Uint128::zero(),
+                    };
+                }
+            }
+        }
+
+        STATS.update(deps.storage, |mut stats| -> Result<_, ContractError> {
+            // A round counts as succeeded once either market pays winners
+            if is_success || bucket.is_some() {
+                stats.rounds_succeeded += 1;
+            } else {
+                stats.rounds_voided += 1;
+            }
+            stats.total_fees = stats.total_fees.checked_add(fee)?.checked_add(bucket_fee)?;
+            Ok(stats)
+        })?;
+
+        // Update the current prediction
+        let mut update_prediction = prediction.clone();
+        if is_success {
+            update_prediction.is_up = if is_flat { None } else { Some(is_up) };
+            update_prediction.is_flat = is_flat;
+            update_prediction.resolved_price = predicted_price;
+        }
+        if bucket.is_some() {
+            update_prediction.resolved_price = predicted_price;
+        }
+        update_prediction.success = is_success;
+        update_prediction.fee = fee;
+        update_prediction.bucket = bucket;
+        update_prediction.bucket_fee = bucket_fee;
+        update_prediction.fixed_outcome = fixed_outcome;
+        update_prediction.claimed = update_prediction
+            .claimed
+            .checked_add(fee)?
+            .checked_add(bucket_fee)?
+            .checked_add(house_return)?;
+        PREDICTIONS.save(
+            deps.storage,
+            &locked_round.to_be_bytes(),
+            &update_prediction,
+        )?;
+
+        state.accrued_fees = state.accrued_fees.checked_add(fund_jackpot(
+            deps.storage,
+            &config,
+            fee.checked_add(bucket_fee)?,
+        )?)?;
+
+        let direction = if is_flat {
+            "flat"
+        } else if is_up {
+            "up"
+        } else {
+            "down"
+        };
+        res.attributes
+            .push(Attribute::new("prediction_id", locked_round.to_string()));
+
+        res.attributes.push(Attribute::new(
+            "locked_price",
+            prediction.locked_price.to_string(),
+        ));
+        res.attributes
+            .push(Attribute::new("is_success", is_success.to_string()));
+
+        if is_success {
+            res.attributes
+                .push(Attribute::new("resolved", direction.to_string()));
+            res.attributes.push(Attribute::new(
+                "resolved_price",
+                predicted_price.to_string(),
+            ));
+        }
+        if let Some(bucket) = bucket {
+            res.attributes
+                .push(Attribute::new("resolved_bucket", bucket.to_string()));
+        }
+    }
+    res.attributes
+        .push(Attribute::new("action", "resolve_prediction"));
+
+    // Update locked price of the current prediction
+    let oracle_price_worker = deps
+        .api
+        .addr_canonicalize(data_price_feed_average.worker.as_str())?;
+    let mut house = HOUSE.load(deps.storage)?;
+    PREDICTIONS.update(
+        deps.storage,
+        &timeframe.open_round.to_be_bytes(),
+        |prediction| -> Result<_, ContractError> {
+            let mut update_prediction = prediction.unwrap();
+            // Seed the empty side so a one-sided round can still be played
+            if update_prediction.up.is_zero() != update_prediction.down.is_zero() {
+                let seed = config.house_seed.min(house.balance);
+                if update_prediction.up.is_zero() {
+                    update_prediction.up = seed;
+                    update_prediction.house_up = seed;
+                } else {
+                    update_prediction.down = seed;
+                    update_prediction.house_down = seed;
+                }
+                house.balance = house.balance.checked_sub(seed)?;
+                house.in_play = house.in_play.checked_add(seed)?;
+            }
             update_prediction.locked_price = predicted_price;
-            update_prediction.oracle_price_worker = Some(deps.api.addr_canonicalize(data_price_feed_average.worker.as_str())?);
+            update_prediction.oracle_price_worker = Some(oracle_price_worker);
             Ok(update_prediction)
         },
     )?;
 
-    // Increment the round
+    HOUSE.save(deps.storage, &house)?;
+
+    // Open the next round of the timeframe
     state.round += 1;
     STATE.save(deps.storage, &state)?;
+    timeframe.locked_round = Some(timeframe.open_round);
+    timeframe.open_round = state.round;
+    TIMEFRAMES.save(deps.storage, &timeframe_id.to_be_bytes(), &timeframe)?;
+    PREDICTIONS.save(
+        deps.storage,
+        &state.round.to_be_bytes(),
+        &open_prediction(&config, timeframe_id, &timeframe, &env),
+    )?;
+
+    // Subscriptions enter the new round from their escrow, a refused bet cancels the
+    // subscription and refunds its escrow so it does not hold a slot forever
+    let subscriptions = SUBSCRIPTIONS
+        .prefix(&timeframe_id.to_be_bytes())
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut subscription_bets = 0u64;
+    let mut refunded = Uint128::zero();
+    for (address, mut subscription) in subscriptions {
+        let bettor = CanonicalAddr::from(address.as_slice());
+        let key = (&timeframe_id.to_be_bytes()[..], address.as_slice());
+        let bet = Bet {
+            bettor: bettor.clone(),
+            side: subscription.side.into(),
+            amount: subscription.amount_per_round,
+            escrowed: true,
+            referrer: None,
+        };
+        if enter_round(deps.branch(), &env, state.round, bet).is_err() {
+            let amount = subscription
+                .amount_per_round
+                .checked_mul(Uint128::from(subscription.rounds_left))?;
+            SUBSCRIPTIONS.remove(deps.storage, key);
+            refunded = refunded.checked_add(amount)?;
+            res = res.add_message(BankMsg::Send {
+                to_address: deps.api.addr_humanize(&bettor)?.to_string(),
+                amount: vec![deduct_tax(
+                    &deps.querier,
+                    Coin {
+                        denom: config.denom.clone(),
+                        amount,
+                    },
+                )?],
+            });
+            continue;
+        }
+        subscription_bets += 1;
+        subscription.rounds_left -= 1;
+        if subscription.rounds_left == 0 {
+            SUBSCRIPTIONS.remove(deps.storage, key);
+        } else {
+            SUBSCRIPTIONS.save(deps.storage, key, &subscription)?;
+        }
+    }
+    if subscription_bets != 0 {
+        res.attributes.push(Attribute::new(
+            "subscription_bets",
+            subscription_bets.to_string(),
+        ));
+    }
+    if !refunded.is_zero() {
+        STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+            state.liabilities = state.liabilities.checked_sub(refunded)?;
+            Ok(state)
+        })?;
+        res.attributes.push(Attribute::new(
+            "subscriptions_refunded",
+            refunded.to_string(),
+        ));
+    }
+
+    Ok(res)
+}
+
+const MAX_SUBSCRIPTIONS: usize = 50;
+#[allow(clippy::too_many_arguments)]
+pub fn try_subscribe(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    side: Outcome,
+    amount_per_round: Uint128,
+    rounds: u64,
+    timeframe: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let timeframe_id = timeframe.unwrap_or(DEFAULT_TIMEFRAME);
+    load_timeframe(deps.storage, timeframe_id)?;
+    let sent = sent_amount(&info, &config.denom)?;
+    if amount_per_round < config.min_subscription_bet
+        || rounds == 0
+        || amount_per_round.checked_mul(Uint128::from(rounds))? != sent
+    {
+        return Err(ContractError::InvalidSubscription {});
+    }
+    let raw_sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let key = (&timeframe_id.to_be_bytes()[..], raw_sender.as_slice());
+    if SUBSCRIPTIONS.may_load(deps.storage, key)?.is_some() {
+        return Err(ContractError::AlreadySubscribed {});
+    }
+    // Every subscription is placed when a round opens, their number is bounded
+    let subscriptions = SUBSCRIPTIONS
+        .prefix(&timeframe_id.to_be_bytes())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .count();
+    if subscriptions >= MAX_SUBSCRIPTIONS {
+        return Err(ContractError::TooManySubscriptions {});
+    }
+
+    SUBSCRIPTIONS.save(
+        deps.storage,
+        key,
+        &Subscription {
+            side,
+            amount_per_round,
+            rounds_left: rounds,
+        },
+    )?;
+    // The escrow is owed to the subscriber until it is bet
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.liabilities = state.liabilities.checked_add(sent)?;
+        Ok(state)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "subscribe")
+        .add_attribute("timeframe", timeframe_id.to_string())
+        .add_attribute("amount_per_round", amount_per_round.to_string())
+        .add_attribute("rounds", rounds.to_string()))
+}
 
-    // Create a new prediction with incremented round
+pub fn try_cancel_subscription(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    timeframe: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let timeframe_id = timeframe.unwrap_or(DEFAULT_TIMEFRAME);
+    let raw_sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let key = (&timeframe_id.to_be_bytes()[..], raw_sender.as_slice());
+    let subscription = SUBSCRIPTIONS
+        .may_load(deps.storage, key)?
+        .ok_or(ContractError::SubscriptionNotFound {})?;
+    let amount = subscription
+        .amount_per_round
+        .checked_mul(Uint128::from(subscription.rounds_left))?;
+
+    SUBSCRIPTIONS.remove(deps.storage, key);
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.liabilities = state.liabilities.checked_sub(amount)?;
+        Ok(state)
+    })?;
+
+    let msg = CosmosMsg::Bank(BankMsg::Send {
+        to_address: info.sender.to_string(),
+        amount: vec![deduct_tax(
+            &deps.querier,
+            Coin {
+                denom: config.denom,
+                amount,
+            },
+        )?],
+    });
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "cancel_subscription")
+        .add_attribute("recipient", info.sender)
+        .add_attribute("amount", amount.to_string()))
+}
+
+const MAX_TIMEFRAMES: usize = 10;
+pub fn try_add_timeframe(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    round_time: u64,
+    limit_time: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    let timeframe_id = TIMEFRAMES
+        .keys(deps.storage, None, None, Order::Ascending)
+        .count();
+    if round_time <= config.lock_buffer + config.reveal_window || timeframe_id >= MAX_TIMEFRAMES {
+        return Err(ContractError::InvalidTimeframe {});
+    }
+    let timeframe_id = timeframe_id as u32;
+
+    let mut state = STATE.load(deps.storage)?;
+    state.round += 1;
+    STATE.save(deps.storage, &state)?;
+    let timeframe = Timeframe {
+        round_time,
+        limit_time,
+        open_round: state.round,
+        locked_round: None,
+    };
+    TIMEFRAMES.save(deps.storage, &timeframe_id.to_be_bytes(), &timeframe)?;
     PREDICTIONS.save(
         deps.storage,
         &state.round.to_be_bytes(),
-        &Prediction {
-            up: Uint128::zero(),
-            down: Uint128::zero(),
-            locked_price: Uint128::zero(),
-            resolved_price: Uint128::zero(),
-            closing_time: env.block.time.plus_seconds(config.round_time).seconds(),
-            expire_time: env
-                .block
-                .time
-                .plus_seconds(config.round_time)
-                .plus_seconds(config.round_time)
-                .plus_seconds(config.limit_time)
-                .seconds(),
-            success: false,
-            is_up: None,
-            oracle_price_worker: None
+        &open_prediction(&config, timeframe_id, &timeframe, &env),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_timeframe")
+        .add_attribute("timeframe", timeframe_id.to_string())
+        .add_attribute("prediction_id", state.round.to_string()))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn try_create_tournament(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    timeframe: Option<u32>,
+    start_round: u64,
+    end_round: u64,
+    entry_fee: Uint128,
+    starting_chips: Uint128,
+    winners: u32,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    let timeframe_id = timeframe.unwrap_or(DEFAULT_TIMEFRAME);
+    let timeframe = load_timeframe(deps.storage, timeframe_id)?;
+    // Rounds already locked cannot be part of a new tournament
+    if start_round < timeframe.open_round
+        || end_round < start_round
+        || end_round - start_round >= MAX_TOURNAMENT_ROUNDS
+        || entry_fee.is_zero()
+        || starting_chips.is_zero()
+        || winners == 0
+    {
+        return Err(ContractError::InvalidTournament {});
+    }
+
+    let tournament_id = TOURNAMENTS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .count() as u64;
+    TOURNAMENTS.save(
+        deps.storage,
+        &tournament_id.to_be_bytes(),
+        &Tournament {
+            timeframe: timeframe_id,
+            start_round,
+            end_round,
+            entry_fee,
+            starting_chips,
+            winners,
+            prize_pool: Uint128::zero(),
+            entrants: 0,
+            settled: false,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "create_tournament")
+        .add_attribute("tournament_id", tournament_id.to_string()))
+}
+
+fn load_tournament(storage: &dyn Storage, tournament_id: u64) -> Result<Tournament, ContractError> {
+    TOURNAMENTS
+        .may_load(storage, &tournament_id.to_be_bytes())?
+        .ok_or(ContractError::TournamentNotFound {})
+}
+
+pub fn try_enter_tournament(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    tournament_id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut tournament = load_tournament(deps.storage, tournament_id)?;
+    // Entries close once the first round is locked
+    let timeframe = load_timeframe(deps.storage, tournament.timeframe)?;
+    if timeframe.open_round > tournament.start_round {
+        return Err(ContractError::TournamentClosed {});
+    }
+    if sent_amount(&info, &config.denom)? != tournament.entry_fee {
+        return Err(ContractError::InvalidEntryFee {});
+    }
+    let raw_sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let key = (&tournament_id.to_be_bytes()[..], raw_sender.as_slice());
+    if TOURNAMENT_ENTRIES.may_load(deps.storage, key)?.is_some() {
+        return Err(ContractError::AlreadyEntered {});
+    }
+    if tournament.entrants >= MAX_TOURNAMENT_ENTRANTS {
+        return Err(ContractError::TooManyEntrants {});
+    }
+
+    TOURNAMENT_ENTRIES.save(
+        deps.storage,
+        key,
+        &TournamentEntry {
+            chips: tournament.starting_chips,
+            bets: vec![],
         },
     )?;
+    tournament.entrants += 1;
+    tournament.prize_pool = tournament.prize_pool.checked_add(tournament.entry_fee)?;
+    TOURNAMENTS.save(deps.storage, &tournament_id.to_be_bytes(), &tournament)?;
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.liabilities = state.liabilities.checked_add(tournament.entry_fee)?;
+        Ok(state)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "enter_tournament")
+        .add_attribute("tournament_id", tournament_id.to_string())
+        .add_attribute("chips", tournament.starting_chips.to_string()))
+}
+
+pub fn try_make_tournament_prediction(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tournament_id: u64,
+    up: bool,
+    chips: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let tournament = load_tournament(deps.storage, tournament_id)?;
+    let round = load_timeframe(deps.storage, tournament.timeframe)?.open_round;
+    if round < tournament.start_round || round > tournament.end_round {
+        return Err(ContractError::TournamentClosed {});
+    }
+    let prediction = PREDICTIONS.load(deps.storage, &round.to_be_bytes())?;
+    let buffer = config.lock_buffer + prediction.reveal_window;
+    if !betting_open(&prediction, env.block.time.seconds(), buffer) {
+        return Err(ContractError::BettingClosed {});
+    }
+    let raw_sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let key = (&tournament_id.to_be_bytes()[..], raw_sender.as_slice());
+    let mut entry = TOURNAMENT_ENTRIES
+        .may_load(deps.storage, key)?
+        .ok_or(ContractError::NotEntered {})?;
+    settle_entry(deps.storage, &mut entry, env.block.time.seconds())?;
+    if chips.is_zero() || chips > entry.chips {
+        return Err(ContractError::InsufficientChips {});
+    }
+
+    entry.chips = entry.chips.checked_sub(chips)?;
+    match entry
+        .bets
+        .iter_mut()
+        .find(|bet| bet.round == round && bet.up == up)
+    {
+        Some(bet) => bet.chips = bet.chips.checked_add(chips)?,
+        None => entry.bets.push(TournamentBet { round, up, chips }),
+    }
+    TOURNAMENT_ENTRIES.save(deps.storage, key, &entry)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "make_tournament_prediction")
+        .add_attribute("tournament_id", tournament_id.to_string())
+        .add_attribute("entered", if up { "up" } else { "down" })
+        .add_attribute("chips", chips.to_string())
+        .add_attribute("prediction_id", round.to_string()))
+}
+
+pub fn try_settle_tournament(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    tournament_id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut tournament = load_tournament(deps.storage, tournament_id)?;
+    if tournament.settled {
+        return Err(ContractError::AlreadyResolved {});
+    }
+    let timeframe = load_timeframe(deps.storage, tournament.timeframe)?;
+    if timeframe.open_round <= tournament.end_round {
+        return Err(ContractError::PredictionStillInProgress {});
+    }
 
+    let entries = TOURNAMENT_ENTRIES
+        .prefix(&tournament_id.to_be_bytes())
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, TournamentEntry)>>>()?;
+    let mut standings = vec![];
+    for (address, mut entry) in entries {
+        settle_entry(deps.storage, &mut entry, env.block.time.seconds())?;
+        if !entry.bets.is_empty() {
+            return Err(ContractError::PredictionStillInProgress {});
+        }
+        TOURNAMENT_ENTRIES.save(
+            deps.storage,
+            (&tournament_id.to_be_bytes()[..], &address),
+            &entry,
+        )?;
+        standings.push((CanonicalAddr::from(address), entry.chips));
+    }
+
+    let shares = prize_shares(standings, tournament.winners, tournament.prize_pool)?;
+    tournament.settled = true;
+    TOURNAMENTS.save(deps.storage, &tournament_id.to_be_bytes(), &tournament)?;
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.liabilities = state.liabilities.checked_sub(tournament.prize_pool)?;
+        Ok(state)
+    })?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "settle_tournament")
+        .add_attribute("tournament_id", tournament_id.to_string());
+    for (address, amount) in shares {
+        let address = deps.api.addr_humanize(&address)?;
+        res = res
+            .add_message(CosmosMsg::Bank(BankMsg::Send {
+                to_address: address.to_string(),
+                amount: vec![deduct_tax(
+                    &deps.querier,
+                    Coin {
+                        denom: config.denom.clone(),
+                        amount,
+                    },
+                )?],
+            }))
+            .add_attribute("winner", address);
+    }
     Ok(res)
 }
 
+fn load_timeframe(storage: &dyn Storage, timeframe: u32) -> Result<Timeframe, ContractError> {
+    TIMEFRAMES
+        .may_load(storage, &timeframe.to_be_bytes())?
+        .ok_or(ContractError::InvalidTimeframe {})
+}
+
+/// New round of a timeframe taking the market settings of the config
+fn open_prediction(
+    config: &Config,
+    timeframe_id: u32,
+    timeframe: &Timeframe,
+    env: &Env,
+) -> Prediction {
+    Prediction {
+        timeframe: timeframe_id,
+        up: Uint128::zero(),
+        down: Uint128::zero(),
+        flat: Uint128::zero(),
+        flat_band: config.flat_band,
+        bucket_bands: config.bucket_bands.clone(),
+        buckets: bucket_pools(&config.bucket_bands),
+        bucket: None,
+        bucket_fee: Uint128::zero(),
+        fixed_multiplier: config.fixed_multiplier,
+        fixed_up: Uint128::zero(),
+        fixed_down: Uint128::zero(),
+        fixed_reserved_up: Uint128::zero(),
+        fixed_reserved_down: Uint128::zero(),
+        fixed_outcome: None,
+        reveal_window: config.reveal_window,
+        reveal_penalty: config.reveal_penalty,
+        committed: Uint128::zero(),
+        locked_price: Uint128::zero(),
+        resolved_price: Uint128::zero(),
+        closing_time: env.block.time.plus_seconds(timeframe.round_time).seconds(),
+        expire_time: env
+            .block
+            .time
+            .plus_seconds(timeframe.round_time)
+            .plus_seconds(timeframe.round_time)
+            .plus_seconds(timeframe.limit_time)
+            .seconds(),
+        success: false,
+        is_up: None,
+        is_flat: false,
+        oracle_price_worker: None,
+        pending_games: 0,
+        claimed: Uint128::zero(),
+        // Fixed before the first bet, an entry fee charged during betting cannot change at lock
+        fee_settings: Some(FeeSettings {
+            model: config.fee_model,
+            rate: config.collector_fee,
+            min_fee: config.min_fee,
+        }),
+        fee: Uint128::zero(),
+        house_up: Uint128::zero(),
+        house_down: Uint128::zero(),
+    }
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::State {} => to_binary(&query_state(deps)?),
         QueryMsg::Game { address, round } => to_binary(&query_game(deps, address, round)?),
-        QueryMsg::Prediction { round } => to_binary(&query_prediction(deps, round)?),
-        QueryMsg::Predictions { start_after, limit } => {
-            to_binary(&query_predictions(deps, start_after, limit)?)
+        QueryMsg::Prediction { round } => to_binary(&query_prediction(deps, env, round)?),
+        QueryMsg::Predictions {
+            start_after,
+            start_before,
+            limit,
+            order,
+            status,
+            pending_claims,
+            timeframe,
+        } => to_binary(&query_predictions(
+            deps,
+            env,
+            start_after,
+            start_before,
+            limit,
+            order,
+            status,
+            pending_claims,
+            timeframe,
+        )?),
+        QueryMsg::CurrentRound { timeframe } => {
+            to_binary(&query_current_round(deps, env, timeframe)?)
+        }
+        QueryMsg::Timeframes {} => to_binary(&query_timeframes(deps)?),
+        QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
+        QueryMsg::Subscription { address, timeframe } => {
+            to_binary(&query_subscription(deps, address, timeframe)?)
         }
+        QueryMsg::Tournament { tournament_id } => {
+            to_binary(&query_tournament(deps, tournament_id)?)
+        }
+        QueryMsg::TournamentEntry {
+            tournament_id,
+            address,
+        } => to_binary(&query_tournament_entry(deps, env, tournament_id, address)?),
+        QueryMsg::Stats {} => to_binary(&query_stats(deps)?),
+        QueryMsg::Solvency {} => to_binary(&query_solvency(deps, env)?),
+        QueryMsg::AccruedFees {} => to_binary(&query_accrued_fees(deps)?),
+        QueryMsg::UserStats { address } => to_binary(&query_user_stats(deps, address)?),
+        QueryMsg::ReferralStats { address } => to_binary(&query_referral_stats(deps, address)?),
+        QueryMsg::House {} => to_binary(&query_house(deps)?),
+        QueryMsg::Jackpot {} => to_binary(&query_jackpot(deps)?),
+        QueryMsg::Streak { address } => to_binary(&query_streak(deps, address)?),
+        QueryMsg::Leaderboard {
+            kind,
+            period,
+            limit,
+        } => to_binary(&query_leaderboard(deps, env, kind, period, limit)?),
     }
 }
 
 fn query_state(deps: Deps) -> StdResult<StateResponse> {
     let state = STATE.load(deps.storage)?;
-    Ok(StateResponse { round: state.round })
+    Ok(StateResponse {
+        round: state.round,
+        dust: state.dust,
+        liabilities: state.liabilities,
+        accrued_fees: state.accrued_fees,
+    })
+}
+fn query_stats(deps: Deps) -> StdResult<StatsResponse> {
+    let stats = STATS.load(deps.storage)?;
+    Ok(StatsResponse {
+        total_volume: stats.total_volume,
+        total_fees: stats.total_fees,
+        total_paid_out: stats.total_paid_out,
+        total_refunded: stats.total_refunded,
+        unique_players: stats.unique_players,
+        rounds_succeeded: stats.rounds_succeeded,
+        rounds_voided: stats.rounds_voided,
+    })
+}
+fn query_solvency(deps: Deps, env: Env) -> StdResult<SolvencyResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+    let balance = deps
+        .querier
+        .query_balance(env.contract.address, config.denom)?
+        .amount;
+    Ok(SolvencyResponse {
+        balance,
+        liabilities: state.liabilities,
+        solvent: balance >= state.liabilities,
+    })
+}
+
+fn query_house(deps: Deps) -> StdResult<HouseResponse> {
+    let house = HOUSE.load(deps.storage)?;
+    Ok(HouseResponse {
+        balance: house.balance,
+        in_play: house.in_play,
+        total_deposited: house.total_deposited,
+        total_withdrawn: house.total_withdrawn,
+        total_won: house.total_won,
+        total_lost: house.total_lost,
+    })
+}
+
+fn query_jackpot(deps: Deps) -> StdResult<JackpotResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let jackpot = JACKPOT.load(deps.storage)?;
+    Ok(JackpotResponse {
+        balance: jackpot.balance,
+        share: config.jackpot_share,
+        streak: config.jackpot_streak,
+        min_bet: config.jackpot_min_bet,
+        total_paid: jackpot.total_paid,
+        winners: jackpot
+            .winners
+            .into_iter()
+            .map(|win| {
+                Ok(JackpotWinResponse {
+                    address: deps.api.addr_humanize(&win.address)?.to_string(),
+                    amount: win.amount,
+                    streak: win.streak,
+                })
+            })
+            .collect::<StdResult<Vec<JackpotWinResponse>>>()?,
+    })
+}
+
+fn query_streak(deps: Deps, address: String) -> StdResult<StreakResponse> {
+    let raw_address = deps.api.addr_canonicalize(&address)?;
+    let streak = STREAKS
+        .may_load(deps.storage, raw_address.as_slice())?
+        .unwrap_or_default();
+    Ok(StreakResponse {
+        address,
+        streak: streak.count,
+        last_round: streak.last_round,
+    })
+}
+
+fn query_accrued_fees(deps: Deps) -> StdResult<AccruedFeesResponse> {
+    let state = STATE.load(deps.storage)?;
+    Ok(AccruedFeesResponse {
+        accrued_fees: state.accrued_fees,
+    })
 }
 fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
     Ok(ConfigResponse {
+        admin: deps.api.addr_humanize(&config.admin)?.to_string(),
         pool_address: deps.api.addr_humanize(&config.pool_address)?.to_string(),
+        collector_address: deps
+            .api
+            .addr_humanize(&config.collector_address)?
+            .to_string(),
         round_time: config.round_time,
         limit_time: config.limit_time,
         denom: config.denom,
+        collector_fee: config.collector_fee,
+        fee_model: config.fee_model,
+        min_fee: config.min_fee,
+        fee_recipients: config
+            .fee_recipients
+            .into_iter()
+            .map(|recipient| {
+                Ok(FeeRecipientMsg {
+                    address: deps.api.addr_humanize(&recipient.address)?.to_string(),
+                    weight: recipient.weight,
+                })
+            })
+            .collect::<StdResult<Vec<FeeRecipientMsg>>>()?,
+        staking_address: config
+            .staking_address
+            .map(|address| deps.api.addr_humanize(&address))
+            .transpose()?
+            .map(|address| address.to_string()),
+        discount_tiers: config.discount_tiers,
+        referral_share: config.referral_share,
+        house_seed: config.house_seed,
+        flat_band: config.flat_band,
+        bucket_bands: config.bucket_bands,
+        fixed_multiplier: config.fixed_multiplier,
+        lock_buffer: config.lock_buffer,
+        reveal_window: config.reveal_window,
+        reveal_penalty: config.reveal_penalty,
+        integrators: config
+            .integrators
+            .iter()
+            .map(|integrator| Ok(deps.api.addr_humanize(integrator)?.to_string()))
+            .collect::<StdResult<Vec<String>>>()?,
+        jackpot_share: config.jackpot_share,
+        jackpot_streak: config.jackpot_streak,
+        jackpot_min_bet: config.jackpot_min_bet,
+        epoch_rounds: config.epoch_rounds,
+        epoch_start: config.epoch_start,
+        leaderboard_min_rounds: config.leaderboard_min_rounds,
+        min_subscription_bet: config.min_subscription_bet,
     })
 }
-fn query_game(deps: Deps, address: String, round: u64) -> StdResult<Game> {
+fn query_game(deps: Deps, address: String, round: u64) -> StdResult<GameResponse> {
     let raw_address = deps.api.addr_canonicalize(&address)?;
-    let game = GAMES.load(
-        deps.storage,
-        (&raw_address.as_slice(), &round.to_be_bytes()),
-    )?;
-    Ok(game)
+    let game = GAMES.load(deps.storage, (raw_address.as_slice(), &round.to_be_bytes()))?;
+    Ok(GameResponse {
+        address,
+        round,
+        up: game.up,
+        down: game.down,
+        flat: game.flat,
+        buckets: game.buckets,
+        fixed_up: game.fixed_up,
+        fixed_down: game.fixed_down,
+        multiplier: game.multiplier,
+        commitment: game.commitment,
+        committed: game.committed,
+        prize: game.prize,
+        resolved: game.resolved,
+        discount: game.discount,
+    })
 }
-fn query_prediction(deps: Deps, round: u64) -> StdResult<Prediction> {
-    let prediction = PREDICTIONS.load(deps.storage, &round.to_be_bytes())?;
-    Ok(prediction)
+
+fn prediction_status(
+    prediction: &Prediction,
+    round: u64,
+    timeframe: &Timeframe,
+    now: u64,
+) -> PredictionStatus {
+    if round == timeframe.open_round {
+        if now >= prediction.closing_time {
+            PredictionStatus::Closed
+        } else {
+            PredictionStatus::Open
+        }
+    } else if Some(round) == timeframe.locked_round {
+        if now >= prediction.expire_time {
+            PredictionStatus::Expired
+        } else {
+            PredictionStatus::Locked
+        }
+    } else if prediction.success || prediction.bucket.is_some() {
+        PredictionStatus::Resolved
+    } else {
+        PredictionStatus::Voided
+    }
 }
 
-const DEFAULT_LIMIT: u32 = 10;
-const MAX_LIMIT: u32 = 30;
-fn query_predictions(
+fn prediction_info(
     deps: Deps,
-    start_after: Option<u64>,
-    limit: Option<u32>,
-) -> StdResult<Vec<(u64, Prediction)>> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = start_after.map(|d| Bound::Exclusive(d.to_be_bytes().to_vec()));
+    env: &Env,
+    round: u64,
+    prediction: Prediction,
+) -> StdResult<PredictionInfo> {
+    let timeframe = TIMEFRAMES.load(deps.storage, &prediction.timeframe.to_be_bytes())?;
+    let status = prediction_status(&prediction, round, &timeframe, env.block.time.seconds());
+    let oracle_price_worker = match prediction.oracle_price_worker {
+        Some(worker) => Some(deps.api.addr_humanize(&worker)?.to_string()),
+        None => None,
+    };
+    Ok(PredictionInfo {
+        round,
+        status,
+        timeframe: prediction.timeframe,
+        up: prediction.up,
+        down: prediction.down,
+        flat: prediction.flat,
+        flat_band: prediction.flat_band,
+        bucket_bands: prediction.bucket_bands,
+        buckets: prediction.buckets,
+        bucket: prediction.bucket,
+        bucket_fee: prediction.bucket_fee,
+        fixed_multiplier: prediction.fixed_multiplier,
+        fixed_up: prediction.fixed_up,
+        fixed_down: prediction.fixed_down,
+        fixed_reserved_up: prediction.fixed_reserved_up,
+        fixed_reserved_down: prediction.fixed_reserved_down,
+        fixed_outcome: prediction.fixed_outcome,
+        reveal_window: prediction.reveal_window,
+        reveal_penalty: prediction.reveal_penalty,
+        committed: prediction.committed,
+        locked_price: prediction.locked_price,
+        resolved_price: prediction.resolved_price,
+        closing_time: prediction.closing_time,
+        expire_time: prediction.expire_time,
+        success: prediction.success,
+        is_up: prediction.is_up,
+        is_flat: prediction.is_flat,
+        oracle_price_worker,
+        pending_games: prediction.pending_games,
+        claimed: prediction.claimed,
+        fee_settings: prediction.fee_settings,
+        fee: prediction.fee,
+        house_up: prediction.house_up,
+        house_down: prediction.house_down,
+    })
+}
 
-    let prediction = PREDICTIONS
-        .range(deps.storage, start, None, Order::Ascending)
-        .take(limit)
-        .map(|pair| {
-            pair.and_then(|(k, prediction)| {
-                Ok((u64::from_be_bytes(k.try_into().unwrap()), prediction))
-            })
-        })
-        .collect::<StdResult<Vec<(u64, Prediction)>>>()?;
-    Ok(prediction)
+fn query_prediction(deps: Deps, env: Env, round: u64) -> StdResult<PredictionInfo> {
+    let prediction = PREDICTIONS.load(deps.storage, &round.to_be_bytes())?;
+    prediction_info(deps, &env, round, prediction)
 }
 
-#[cfg(test)]
-mod tests {
+/// Bets close `lock_buffer` seconds before the closing time, and stay closed until resolution
+fn betting_open(prediction: &Prediction, now: u64, lock_buffer: u64) -> bool {
+    now.saturating_add(lock_buffer) < prediction.closing_time
+}
 
-    use super::*;
+fn query_current_round(
+    deps: Deps,
+    env: Env,
+    timeframe: Option<u32>,
+) -> StdResult<CurrentRoundResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let timeframe = TIMEFRAMES.load(
+        deps.storage,
+        &timeframe.unwrap_or(DEFAULT_TIMEFRAME).to_be_bytes(),
+    )?;
+    let now = env.block.time.seconds();
+    let prediction = PREDICTIONS.load(deps.storage, &timeframe.open_round.to_be_bytes())?;
+    let locked_round = match timeframe.locked_round {
+        Some(round) => {
+            let locked = PREDICTIONS.load(deps.storage, &round.to_be_bytes())?;
+            Some(prediction_info(deps, &env, round, locked)?)
+        }
+        None => None,
+    };
+
+    Ok(CurrentRoundResponse {
+        seconds_until_lock: prediction.closing_time.saturating_sub(now),
+        betting_open: betting_open(
+            &prediction,
+            now,
+            config.lock_buffer + prediction.reveal_window,
+        ),
+        up: prediction.up,
+        down: prediction.down,
+        flat: prediction.flat,
+        total: prediction
+            .up
+            .checked_add(prediction.down)?
+            .checked_add(prediction.flat)?,
+        open_round: prediction_info(deps, &env, timeframe.open_round, prediction)?,
+        locked_round,
+    })
+}
+
+fn query_timeframes(deps: Deps) -> StdResult<TimeframesResponse> {
+    let timeframes = TIMEFRAMES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|pair| {
+            let (k, timeframe) = pair?;
+            Ok(TimeframeResponse {
+                timeframe: u32::from_be_bytes(k.try_into().unwrap()),
+                round_time: timeframe.round_time,
+                limit_time: timeframe.limit_time,
+                open_round: timeframe.open_round,
+                locked_round: timeframe.locked_round,
+            })
+        })
+        .collect::<StdResult<Vec<TimeframeResponse>>>()?;
+    Ok(TimeframesResponse { timeframes })
+}
+
+fn query_tournament(deps: Deps, tournament_id: u64) -> StdResult<TournamentResponse> {
+    let tournament = TOURNAMENTS.load(deps.storage, &tournament_id.to_be_bytes())?;
+    Ok(TournamentResponse {
+        tournament_id,
+        timeframe: tournament.timeframe,
+        start_round: tournament.start_round,
+        end_round: tournament.end_round,
+        entry_fee: tournament.entry_fee,
+        starting_chips: tournament.starting_chips,
+        winners: tournament.winners,
+        prize_pool: tournament.prize_pool,
+        entrants: tournament.entrants,
+        settled: tournament.settled,
+    })
+}
+
+fn query_tournament_entry(
+    deps: Deps,
+    env: Env,
+    tournament_id: u64,
+    address: String,
+) -> StdResult<TournamentEntryResponse> {
+    let raw_address = deps.api.addr_canonicalize(&address)?;
+    let mut entry = TOURNAMENT_ENTRIES.load(
+        deps.storage,
+        (&tournament_id.to_be_bytes()[..], raw_address.as_slice()),
+    )?;
+    settle_entry(deps.storage, &mut entry, env.block.time.seconds())?;
+    Ok(TournamentEntryResponse {
+        tournament_id,
+        address,
+        chips: entry.chips,
+        bets: entry.bets,
+    })
+}
+
+fn query_balance(deps: Deps, address: String) -> StdResult<BalanceResponse> {
+    let raw_address = deps.api.addr_canonicalize(&address)?;
+    let balance = BALANCES
+        .may_load(deps.storage, raw_address.as_slice())?
+        .unwrap_or_default();
+    let auto_compound = AUTO_COMPOUNDS.may_load(deps.storage, raw_address.as_slice())?;
+    Ok(BalanceResponse {
+        address,
+        balance,
+        auto_compound: auto_compound.is_some(),
+        timeframe: auto_compound
+            .as_ref()
+            .map(|auto_compound| auto_compound.timeframe),
+        side: auto_compound.and_then(|auto_compound| auto_compound.side),
+    })
+}
+
+fn query_subscription(
+    deps: Deps,
+    address: String,
+    timeframe: Option<u32>,
+) -> StdResult<SubscriptionResponse> {
+    let raw_address = deps.api.addr_canonicalize(&address)?;
+    let timeframe = timeframe.unwrap_or(DEFAULT_TIMEFRAME);
+    let subscription = SUBSCRIPTIONS.load(
+        deps.storage,
+        (&timeframe.to_be_bytes()[..], raw_address.as_slice()),
+    )?;
+    Ok(SubscriptionResponse {
+        address,
+        timeframe,
+        side: subscription.side,
+        amount_per_round: subscription.amount_per_round,
+        rounds_left: subscription.rounds_left,
+        balance: subscription
+            .amount_per_round
+            .checked_mul(Uint128::from(subscription.rounds_left))?,
+    })
+}
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+const MAX_SCAN: usize = 100;
+#[allow(clippy::too_many_arguments)]
+fn query_predictions(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    start_before: Option<u64>,
+    limit: Option<u32>,
+    order: Option<OrderBy>,
+    status: Option<PredictionStatus>,
+    pending_claims: Option<bool>,
+    timeframe: Option<u32>,
+) -> StdResult<PredictionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|d| Bound::Exclusive(d.to_be_bytes().to_vec()));
+    let end = start_before.map(|d| Bound::Exclusive(d.to_be_bytes().to_vec()));
+    let order = order.unwrap_or(OrderBy::Ascending).into();
+    let now = env.block.time.seconds();
+
+    let mut predictions = vec![];
+    let mut next_start = None;
+    let mut last_round = None;
+    for (scanned, pair) in PREDICTIONS
+        .range(deps.storage, start, end, order)
+        .enumerate()
+    {
+        let (k, prediction) = pair?;
+        // Filters may skip most rounds, so the rows scanned are bounded too
+        if predictions.len() == limit || scanned == MAX_SCAN {
+            next_start = last_round;
+            break;
+        }
+        let round = u64::from_be_bytes(k.try_into().unwrap());
+        last_round = Some(round);
+        let prediction = prediction_info(deps, &env, round, prediction)?;
+        let claimable = prediction.expire_time <= now && prediction.pending_games != 0;
+        if (status.is_none() || status == Some(prediction.status))
+            && (pending_claims.is_none() || pending_claims == Some(claimable))
+            && (timeframe.is_none() || timeframe == Some(prediction.timeframe))
+        {
+            predictions.push(prediction);
+        }
+    }
+    Ok(PredictionsResponse {
+        predictions,
+        next_start,
+    })
+}
+
+fn user_stats_response(
+    deps: Deps,
+    address: &CanonicalAddr,
+    stats: UserStats,
+) -> StdResult<UserStatsResponse> {
+    Ok(UserStatsResponse {
+        address: deps.api.addr_humanize(address)?.to_string(),
+        rounds_played: stats.rounds_played,
+        rounds_won: stats.rounds_won,
+        rounds_refunded: stats.rounds_refunded,
+        volume: stats.volume,
+        payout: stats.payout,
+    })
+}
+
+fn query_user_stats(deps: Deps, address: String) -> StdResult<UserStatsResponse> {
+    let raw_address = deps.api.addr_canonicalize(&address)?;
+    let stats = USERS
+        .may_load(deps.storage, raw_address.as_slice())?
+        .unwrap_or_default();
+    user_stats_response(deps, &raw_address, stats)
+}
+
+fn query_referral_stats(deps: Deps, address: String) -> StdResult<ReferralStatsResponse> {
+    let raw_address = deps.api.addr_canonicalize(&address)?;
+    let referral = REFERRALS
+        .may_load(deps.storage, raw_address.as_slice())?
+        .unwrap_or_default();
+    Ok(ReferralStatsResponse {
+        address,
+        referred_users: referral.referred_users,
+        total_rewards: referral.total_rewards,
+        pending_rewards: referral.pending_rewards,
+    })
+}
+
+fn query_leaderboard(
+    deps: Deps,
+    env: Env,
+    kind: LeaderboardKind,
+    period: LeaderboardPeriod,
+    limit: Option<u32>,
+) -> StdResult<LeaderboardResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let key = kind_key(kind);
+    let (epoch, board) = match period {
+        LeaderboardPeriod::AllTime {} => (None, LEADERBOARDS.may_load(deps.storage, key)?),
+        LeaderboardPeriod::Epoch { epoch } => {
+            let epoch = match epoch {
+                Some(epoch) => epoch,
+                None => epoch_at(&CONFIG.load(deps.storage)?, env.block.time.seconds()),
+            };
+            (
+                Some(epoch),
+                EPOCH_LEADERBOARDS.may_load(deps.storage, (key, &epoch.to_be_bytes()))?,
+            )
+        }
+    };
+
+    let entries = board
+        .unwrap_or_default()
+        .into_iter()
+        .take(limit)
+        .map(|entry| user_stats_response(deps, &entry.address, entry.stats))
+        .collect::<StdResult<Vec<UserStatsResponse>>>()?;
+    Ok(LeaderboardResponse {
+        kind,
+        epoch,
+        entries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
     use crate::mock_querier::mock_dependencies_custom;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{attr, coins, from_binary, Addr, Api, Attribute, Coin};
-    use std::ops::Add;
+    use cosmwasm_std::testing::{mock_env, mock_info};
+    use cosmwasm_std::{coins, Addr, Api, Attribute, Coin};
     use std::str::FromStr;
 
     #[test]
     fn proper_initialization() {
-        let mut deps = mock_dependencies_custom(&[]);
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
         deps.querier.pool_token(
             Uint128::new(10_250_000_000u128),
             Uint128::new(955_000_000u128),
@@ -534,6 +3219,11 @@ mod tests {
             limit_time: 30,
             denom: "uusd".to_string(),
             collector_ratio: Decimal::from_str("0.05").unwrap(),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
         };
         let info = mock_info("creator", &coins(1000, "earth"));
 
@@ -563,6 +3253,10 @@ mod tests {
         assert!(!prediction.success);
         assert_eq!(prediction.is_up, None);
         assert_eq!(prediction.locked_price, Uint128::zero());
+
+        let config = query_config(deps.as_ref()).unwrap();
+        assert_eq!(config.collector_address, "collector");
+        assert_eq!(config.collector_fee, Decimal::from_str("0.05").unwrap());
         // it worked, let's query the state
         // let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCount {}).unwrap();
         // let value: CountResponse = from_binary(&res).unwrap();
@@ -571,7 +3265,7 @@ mod tests {
 
     #[test]
     fn proper_make_prediction() {
-        let mut deps = mock_dependencies_custom(&[]);
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
         deps.querier.pool_token(
             Uint128::new(10_250_000_000u128),
             Uint128::new(955_000_000u128),
@@ -583,12 +3277,22 @@ mod tests {
             limit_time: 30,
             denom: "uusd".to_string(),
             collector_ratio: Decimal::from_str("0.05").unwrap(),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
         };
         let info = mock_info("creator", &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // Player1 Enter up
-        let msg = ExecuteMsg::MakePrediction { up: false };
+        let msg = ExecuteMsg::MakePrediction {
+            side: Outcome::Down,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
         let info = mock_info("player1", &[]);
         let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
         assert_eq!(err, ContractError::EmptyFunds {});
@@ -619,16 +3323,21 @@ mod tests {
         let game = GAMES
             .load(
                 deps.as_ref().storage,
-                (&sender.as_slice(), &0_u64.to_be_bytes()),
+                (sender.as_slice(), &0_u64.to_be_bytes()),
             )
             .unwrap();
         assert_eq!(game.up, Uint128::zero());
         assert_eq!(game.down, Uint128::from(100_000_000u128));
-        assert_eq!(game.resolved, false);
+        assert!(!game.resolved);
         assert_eq!(game.prize, Uint128::zero());
 
         // Player2 Enter up
-        let msg = ExecuteMsg::MakePrediction { up: true };
+        let msg = ExecuteMsg::MakePrediction {
+            side: Outcome::Up,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
         let info = mock_info(
             "player2",
             &[Coin {
@@ -656,16 +3365,21 @@ mod tests {
         let game = GAMES
             .load(
                 deps.as_ref().storage,
-                (&sender.as_slice(), &0_u64.to_be_bytes()),
+                (sender.as_slice(), &0_u64.to_be_bytes()),
             )
             .unwrap();
         assert_eq!(game.up, Uint128::from(500_000_000u128));
         assert_eq!(game.down, Uint128::zero());
-        assert_eq!(game.resolved, false);
+        assert!(!game.resolved);
         assert_eq!(game.prize, Uint128::zero());
 
         // Player2 Enter down
-        let msg = ExecuteMsg::MakePrediction { up: false };
+        let msg = ExecuteMsg::MakePrediction {
+            side: Outcome::Down,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
         let info = mock_info(
             "player2",
             &[Coin {
@@ -693,12 +3407,12 @@ mod tests {
         let game = GAMES
             .load(
                 deps.as_ref().storage,
-                (&sender.as_slice(), &0_u64.to_be_bytes()),
+                (sender.as_slice(), &0_u64.to_be_bytes()),
             )
             .unwrap();
         assert_eq!(game.up, Uint128::from(500_000_000u128));
         assert_eq!(game.down, Uint128::from(100_000_000u128));
-        assert_eq!(game.resolved, false);
+        assert!(!game.resolved);
         assert_eq!(game.prize, Uint128::zero());
 
         // Query prediction
@@ -707,11 +3421,19 @@ mod tests {
             .unwrap();
         assert_eq!(prediction.down, Uint128::from(200_000_000u128));
         assert_eq!(prediction.up, Uint128::from(500_000_000u128));
+
+        let current = query_current_round(deps.as_ref(), mock_env(), None).unwrap();
+        assert_eq!(current.open_round.round, 0);
+        assert_eq!(current.open_round.status, PredictionStatus::Open);
+        assert_eq!(current.locked_round, None);
+        assert_eq!(current.seconds_until_lock, 300);
+        assert!(current.betting_open);
+        assert_eq!(current.total, Uint128::from(700_000_000u128));
     }
 
     #[test]
     fn proper_resolve_prediction() {
-        let mut deps = mock_dependencies_custom(&[]);
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
         deps.querier.pool_token(
             Uint128::new(10_250_000_000u128),
             Uint128::new(955_000_000u128),
@@ -724,16 +3446,26 @@ mod tests {
             limit_time: 30,
             denom: "uusd".to_string(),
             collector_ratio: Decimal::from_str("0.05").unwrap(),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
         };
         let info = mock_info("creator", &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         deps.querier.pool_token(
             Uint128::new(15_250_000_000u128),
             Uint128::new(555_000_000u128),
         );
         // Player1 enter down
-        let msg = ExecuteMsg::MakePrediction { up: false };
+        let msg = ExecuteMsg::MakePrediction {
+            side: Outcome::Down,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
         let info = mock_info(
             "player1",
             &[Coin {
@@ -741,10 +3473,15 @@ mod tests {
                 amount: Uint128::from(100_000_000u128),
             }],
         );
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // Player2 Enter up
-        let msg = ExecuteMsg::MakePrediction { up: true };
+        let msg = ExecuteMsg::MakePrediction {
+            side: Outcome::Up,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
         let info = mock_info(
             "player2",
             &[Coin {
@@ -752,10 +3489,15 @@ mod tests {
                 amount: Uint128::from(500_000_000u128),
             }],
         );
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // Player2 Enter down
-        let msg = ExecuteMsg::MakePrediction { up: false };
+        let msg = ExecuteMsg::MakePrediction {
+            side: Outcome::Down,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
         let info = mock_info(
             "player2",
             &[Coin {
@@ -763,10 +3505,10 @@ mod tests {
                 amount: Uint128::from(100_000_000u128),
             }],
         );
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // Resolve prediction
-        let msg = ExecuteMsg::ResolvePrediction {};
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
         let err = execute(
             deps.as_mut(),
             mock_env(),
@@ -806,7 +3548,12 @@ mod tests {
             Uint128::new(455_000_000u128),
         );
         // Player1 enter down
-        let msg = ExecuteMsg::MakePrediction { up: false };
+        let msg = ExecuteMsg::MakePrediction {
+            side: Outcome::Down,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
         let info = mock_info(
             "player1",
             &[Coin {
@@ -819,7 +3566,7 @@ mod tests {
 
         // Resolve
         env.block.time = env.block.time.plus_seconds(config.round_time);
-        let msg = ExecuteMsg::ResolvePrediction {};
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
         let res = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
         assert_eq!(
             res.attributes,
@@ -838,7 +3585,12 @@ mod tests {
             Uint128::new(255_000_000u128),
         );
         // Player1 enter down
-        let msg = ExecuteMsg::MakePrediction { up: false };
+        let msg = ExecuteMsg::MakePrediction {
+            side: Outcome::Down,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
         let info = mock_info(
             "player1",
             &[Coin {
@@ -846,7 +3598,7 @@ mod tests {
                 amount: Uint128::from(100_000_000u128),
             }],
         );
-        let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
         let info = mock_info(
             "player2",
             &[Coin {
@@ -859,8 +3611,8 @@ mod tests {
 
         // Resolve
         env.block.time = env.block.time.plus_seconds(config.round_time);
-        let msg = ExecuteMsg::ResolvePrediction {};
-        let res = execute(deps.as_mut(), env, mock_info("bot", &[]), msg).unwrap();
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
         println!("{:?}", res);
         assert_eq!(
             res.attributes,
@@ -871,11 +3623,122 @@ mod tests {
                 Attribute::new("action", "resolve_prediction")
             ]
         );
+
+        let res = query_predictions(
+            deps.as_ref(),
+            env.clone(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let status: Vec<(u64, PredictionStatus)> = res
+            .predictions
+            .iter()
+            .map(|prediction| (prediction.round, prediction.status))
+            .collect();
+        assert_eq!(
+            status,
+            vec![
+                (0, PredictionStatus::Resolved),
+                (1, PredictionStatus::Voided),
+                (2, PredictionStatus::Locked),
+                (3, PredictionStatus::Open)
+            ]
+        );
+        assert_eq!(
+            res.predictions[0].oracle_price_worker,
+            Some("worker".to_string())
+        );
+
+        // Latest rounds first
+        let res = query_predictions(
+            deps.as_ref(),
+            env.clone(),
+            None,
+            Some(3),
+            Some(2),
+            Some(OrderBy::Descending),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let rounds: Vec<u64> = res.predictions.iter().map(|p| p.round).collect();
+        assert_eq!(rounds, vec![2, 1]);
+        assert_eq!(res.next_start, Some(1));
+
+        let res = query_predictions(
+            deps.as_ref(),
+            env.clone(),
+            None,
+            None,
+            None,
+            None,
+            Some(PredictionStatus::Voided),
+            None,
+            None,
+        )
+        .unwrap();
+        let rounds: Vec<u64> = res.predictions.iter().map(|p| p.round).collect();
+        assert_eq!(rounds, vec![1]);
+        assert_eq!(res.next_start, None);
+
+        let res = query_predictions(
+            deps.as_ref(),
+            env,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(true),
+            None,
+        )
+        .unwrap();
+        let rounds: Vec<u64> = res.predictions.iter().map(|p| p.round).collect();
+        assert_eq!(rounds, vec![0]);
+        assert_eq!(res.predictions[0].pending_games, 2);
+
+        // The open round is closed once its closing time passed
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(config.round_time * 4);
+        let prediction = query_prediction(deps.as_ref(), env.clone(), 3).unwrap();
+        assert_eq!(prediction.status, PredictionStatus::Closed);
+
+        // A selective filter stops after the scan limit and returns where to continue
+        let resolved = PREDICTIONS
+            .load(deps.as_ref().storage, &0_u64.to_be_bytes())
+            .unwrap();
+        for round in 4..200u64 {
+            PREDICTIONS
+                .save(deps.as_mut().storage, &round.to_be_bytes(), &resolved)
+                .unwrap();
+        }
+        let res = query_predictions(
+            deps.as_ref(),
+            env,
+            None,
+            None,
+            None,
+            None,
+            Some(PredictionStatus::Voided),
+            None,
+            None,
+        )
+        .unwrap();
+        let rounds: Vec<u64> = res.predictions.iter().map(|p| p.round).collect();
+        assert_eq!(rounds, vec![1]);
+        assert_eq!(res.next_start, Some(99));
     }
 
     #[test]
     fn proper_resolve_game() {
-        let mut deps = mock_dependencies_custom(&[]);
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
         deps.querier.pool_token(
             Uint128::new(10_250_000_000u128),
             Uint128::new(955_000_000u128),
@@ -888,9 +3751,14 @@ mod tests {
             limit_time: 30,
             denom: "uusd".to_string(),
             collector_ratio: Decimal::from_str("0.05").unwrap(),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
         };
         let info = mock_info("creator", &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         deps.querier.pool_token(
             Uint128::new(15_250_000_000u128),
@@ -898,7 +3766,12 @@ mod tests {
         );
 
         // Player1 enter down
-        let msg = ExecuteMsg::MakePrediction { up: false };
+        let msg = ExecuteMsg::MakePrediction {
+            side: Outcome::Down,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
         let info = mock_info(
             "player1",
             &[Coin {
@@ -906,10 +3779,15 @@ mod tests {
                 amount: Uint128::from(100_000_000u128),
             }],
         );
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // Player2 Enter up
-        let msg = ExecuteMsg::MakePrediction { up: true };
+        let msg = ExecuteMsg::MakePrediction {
+            side: Outcome::Up,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
         let info = mock_info(
             "player2",
             &[Coin {
@@ -917,10 +3795,15 @@ mod tests {
                 amount: Uint128::from(500_000_000u128),
             }],
         );
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // Player2 Enter down
-        let msg = ExecuteMsg::MakePrediction { up: false };
+        let msg = ExecuteMsg::MakePrediction {
+            side: Outcome::Down,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
         let info = mock_info(
             "player2",
             &[Coin {
@@ -928,9 +3811,9 @@ mod tests {
                 amount: Uint128::from(100_000_000u128),
             }],
         );
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        let msg = ExecuteMsg::ResolvePrediction {};
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
         let config = CONFIG.load(deps.as_ref().storage).unwrap();
         let mut env = mock_env();
         env.block.time = env.block.time.plus_seconds(config.round_time);
@@ -952,9 +3835,9 @@ mod tests {
             Uint128::new(1_250_000_000u128),
             Uint128::new(955_000_000u128),
         );
-        let msg = ExecuteMsg::ResolvePrediction {};
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
         env.block.time = env.block.time.plus_seconds(config.round_time);
-        let res = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
         env.block.time = env
             .block
             .time
@@ -977,18 +3860,7 @@ mod tests {
             to_address: "player1".to_string(),
             amount: vec![Coin::new(331_500_000, "uusd")],
         };
-        let msg_bank_fee = BankMsg::Send {
-            to_address: "collector".to_string(),
-            amount: vec![Coin::new(17_326_732, "uusd")],
-        };
-
-        assert_eq!(
-            res.messages,
-            vec![
-                SubMsg::new(CosmosMsg::Bank(msg_bank)),
-                SubMsg::new(CosmosMsg::Bank(msg_bank_fee))
-            ]
-        );
+        assert_eq!(res.messages, vec![SubMsg::new(CosmosMsg::Bank(msg_bank))]);
         assert_eq!(
             res.attributes,
             vec![
@@ -997,6 +3869,34 @@ mod tests {
             ]
         );
 
+        // Fees accrue until they are distributed, the collector receives all by default
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.accrued_fees, Uint128::from(17_500_000u128));
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::DistributeFees {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "collector".to_string(),
+                amount: vec![Coin::new(17_326_732, "uusd")],
+            }))]
+        );
+        let state = query_state(deps.as_ref()).unwrap();
+        assert!(state.accrued_fees.is_zero());
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::DistributeFees {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::EmptyFunds {});
+
         // Handle resolve multiple times
         let err = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap_err();
         assert_eq!(err, ContractError::AlreadyResolved {});
@@ -1013,7 +3913,12 @@ mod tests {
         );
 
         // Player1 enter down
-        let msg = ExecuteMsg::MakePrediction { up: false };
+        let msg = ExecuteMsg::MakePrediction {
+            side: Outcome::Down,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
         let info = mock_info(
             "player1",
             &[Coin {
@@ -1021,9 +3926,9 @@ mod tests {
                 amount: Uint128::from(100_000_000u128),
             }],
         );
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        let msg = ExecuteMsg::ResolvePrediction {};
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
         //env.block.time = env.block.time.plus_seconds(config.round_time);
         let res = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
         assert_eq!(res.messages.len(), 0);
@@ -1069,5 +3974,3897 @@ mod tests {
                 Attribute::new("recipient", "player1")
             ]
         );
+
+        let stats = query_stats(deps.as_ref()).unwrap();
+        assert_eq!(stats.total_volume, Uint128::from(800_000_000u128));
+        assert_eq!(stats.total_fees, Uint128::from(17_500_000u128));
+        assert_eq!(stats.total_paid_out, Uint128::from(332_500_000u128));
+        assert_eq!(stats.total_refunded, Uint128::from(100_000_000u128));
+        assert_eq!(stats.unique_players, 2);
+        assert_eq!(stats.rounds_succeeded, 1);
+        assert_eq!(stats.rounds_voided, 1);
+    }
+
+    #[test]
+    fn proper_leaderboard() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::from_str("0.05").unwrap(),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        // Player1 enter down, player2 enter up and down
+        let bets = vec![
+            ("player1", Outcome::Down, 100_000_000u128),
+            ("player2", Outcome::Up, 500_000_000u128),
+            ("player2", Outcome::Down, 100_000_000u128),
+        ];
+        for (player, side, amount) in bets {
+            let info = mock_info(player, &[Coin::new(amount, "uusd")]);
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info,
+                ExecuteMsg::MakePrediction {
+                    side,
+                    referrer: None,
+                    timeframe: None,
+                    amount: None,
+                },
+            )
+            .unwrap();
+        }
+
+        // Lock the round then resolve it down
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        deps.querier.pool_token(
+            Uint128::new(1_250_000_000u128),
+            Uint128::new(955_000_000u128),
+        );
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+        env.block.time = env
+            .block
+            .time
+            .plus_seconds(config.round_time)
+            .plus_seconds(config.limit_time);
+
+        for player in ["player1", "player2"] {
+            let msg = ExecuteMsg::ResolveGame {
+                address: player.to_string(),
+                round: vec![0],
+            };
+            execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+        }
+
+        let stats = query_user_stats(deps.as_ref(), "player2".to_string()).unwrap();
+        assert_eq!(stats.rounds_played, 1);
+        assert_eq!(stats.rounds_won, 1);
+        assert_eq!(stats.volume, Uint128::from(600_000_000u128));
+        assert_eq!(stats.payout, Uint128::from(332_500_000u128));
+
+        // Only player1 is in profit
+        let board = query_leaderboard(
+            deps.as_ref(),
+            env.clone(),
+            LeaderboardKind::NetWinnings,
+            LeaderboardPeriod::AllTime {},
+            None,
+        )
+        .unwrap();
+        assert_eq!(board.epoch, None);
+        assert_eq!(board.entries.len(), 1);
+        assert_eq!(board.entries[0].address, "player1");
+
+        let board = query_leaderboard(
+            deps.as_ref(),
+            env.clone(),
+            LeaderboardKind::Volume,
+            LeaderboardPeriod::Epoch { epoch: None },
+            None,
+        )
+        .unwrap();
+        assert_eq!(board.epoch, Some(0));
+        let addresses: Vec<String> = board.entries.into_iter().map(|e| e.address).collect();
+        assert_eq!(addresses, vec!["player2", "player1"]);
+
+        let board = query_leaderboard(
+            deps.as_ref(),
+            env.clone(),
+            LeaderboardKind::WinRate,
+            LeaderboardPeriod::Epoch { epoch: Some(1) },
+            None,
+        )
+        .unwrap();
+        assert!(board.entries.is_empty());
+    }
+
+    #[test]
+    fn proper_sweep_dust() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::from_str("0.05").unwrap(),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        deps.querier.pool_token(
+            Uint128::new(1_000_000_000u128),
+            Uint128::new(1_000_000_000u128),
+        );
+        let bets = vec![
+            ("player1", Outcome::Up, 100u128),
+            ("player2", Outcome::Up, 200u128),
+            ("player3", Outcome::Down, 1_000u128),
+        ];
+        for (player, side, amount) in bets.clone() {
+            let info = mock_info(player, &[Coin::new(amount, "uusd")]);
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info,
+                ExecuteMsg::MakePrediction {
+                    side,
+                    referrer: None,
+                    timeframe: None,
+                    amount: None,
+                },
+            )
+            .unwrap();
+        }
+
+        // Lock the round then resolve it up
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        deps.querier.pool_token(
+            Uint128::new(2_000_000_000u128),
+            Uint128::new(1_000_000_000u128),
+        );
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+        env.block.time = env
+            .block
+            .time
+            .plus_seconds(config.round_time)
+            .plus_seconds(config.limit_time);
+
+        for (player, _, _) in bets {
+            let msg = ExecuteMsg::ResolveGame {
+                address: player.to_string(),
+                round: vec![0],
+            };
+            execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+        }
+
+        // 433 + 866 paid out of a pool of 1300
+        let prediction = PREDICTIONS
+            .load(deps.as_ref().storage, &0_u64.to_be_bytes())
+            .unwrap();
+        assert_eq!(prediction.claimed, Uint128::from(1_299u128));
+        assert_eq!(prediction.pending_games, 0);
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.dust, Uint128::from(1u128));
+
+        let msg = ExecuteMsg::SweepDust {};
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.attributes,
+            vec![
+                Attribute::new("action", "sweep_dust"),
+                Attribute::new("recipient", "collector"),
+                Attribute::new("amount", "1")
+            ]
+        );
+        let state = query_state(deps.as_ref()).unwrap();
+        assert!(state.dust.is_zero());
+        assert_eq!(state.liabilities, state.accrued_fees);
+
+        let err = execute(deps.as_mut(), env, mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::EmptyFunds {});
+    }
+
+    #[test]
+    fn proper_solvency() {
+        let mut deps = mock_dependencies_custom(&[]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::from_str("0.05").unwrap(),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::MakePrediction {
+            side: Outcome::Up,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
+        // The bank has credited the attached funds when the contract runs
+        deps.querier
+            .contract_balance(&[Coin::new(100_000_000, "uusd")]);
+        let info = mock_info("player1", &[Coin::new(100_000_000, "uusd")]);
+        execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+
+        let solvency = query_solvency(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(solvency.balance, Uint128::from(100_000_000u128));
+        assert_eq!(solvency.liabilities, Uint128::from(100_000_000u128));
+        assert!(solvency.solvent);
+
+        // The balance falls below what is already owed
+        deps.querier
+            .contract_balance(&[Coin::new(80_000_000, "uusd")]);
+        let solvency = query_solvency(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(solvency.balance, Uint128::from(80_000_000u128));
+        assert!(!solvency.solvent);
+
+        // New funds cover the new bet but not the existing shortfall
+        deps.querier
+            .contract_balance(&[Coin::new(130_000_000, "uusd")]);
+        let info = mock_info("player2", &[Coin::new(50_000_000, "uusd")]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Insolvent {});
+
+        // Once the shortfall is topped up bets are accepted again
+        deps.querier
+            .contract_balance(&[Coin::new(150_000_000, "uusd")]);
+        let info = mock_info("player2", &[Coin::new(50_000_000, "uusd")]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let solvency = query_solvency(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(solvency.liabilities, Uint128::from(150_000_000u128));
+        assert!(solvency.solvent);
+    }
+
+    #[test]
+    fn proper_fee_models() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::from_str("0.05").unwrap(),
+            fee_model: FeeModel::LosingPool,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let bets = vec![
+            ("player1", Outcome::Down, 100_000_000u128),
+            ("player2", Outcome::Up, 500_000_000u128),
+            ("player2", Outcome::Down, 100_000_000u128),
+        ];
+        for (player, side, amount) in bets {
+            let info = mock_info(player, &[Coin::new(amount, "uusd")]);
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info,
+                ExecuteMsg::MakePrediction {
+                    side,
+                    referrer: None,
+                    timeframe: None,
+                    amount: None,
+                },
+            )
+            .unwrap();
+        }
+
+        // Lock the round with the losing pool fee
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+
+        // Config changes do not apply to the locked round
+        let update = ExecuteMsg::UpdateConfig {
+            collector_address: None,
+            collector_ratio: Some(Decimal::percent(50)),
+            fee_model: Some(FeeModel::Winnings),
+            min_fee: None,
+            fee_recipients: None,
+            staking_address: None,
+            discount_tiers: None,
+            referral_share: None,
+            house_seed: None,
+            flat_band: None,
+            bucket_bands: None,
+            fixed_multiplier: None,
+            lock_buffer: None,
+            reveal_window: None,
+            reveal_penalty: None,
+            integrators: None,
+            jackpot_share: None,
+            jackpot_streak: None,
+            jackpot_min_bet: None,
+            min_subscription_bet: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[]),
+            update.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            update,
+        )
+        .unwrap();
+
+        // Resolve down, 5% of the up pool goes to the collector
+        deps.querier.pool_token(
+            Uint128::new(1_250_000_000u128),
+            Uint128::new(955_000_000u128),
+        );
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 0);
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.accrued_fees, Uint128::from(25_000_000u128));
+        let prediction = PREDICTIONS
+            .load(deps.as_ref().storage, &0_u64.to_be_bytes())
+            .unwrap();
+        assert_eq!(prediction.fee, Uint128::from(25_000_000u128));
+
+        // Player1 shares the 675M left with player2 without any winnings fee
+        let open_env = env.clone();
+        env.block.time = env
+            .block
+            .time
+            .plus_seconds(config.round_time)
+            .plus_seconds(config.limit_time);
+        let msg = ExecuteMsg::ResolveGame {
+            address: "player1".to_string(),
+            round: vec![0],
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "player1".to_string(),
+                amount: vec![Coin::new(336_500_000, "uusd")],
+            }))]
+        );
+
+        // Entry fee is taken from the bet
+        let update = ExecuteMsg::UpdateConfig {
+            collector_address: None,
+            collector_ratio: Some(Decimal::percent(5)),
+            fee_model: Some(FeeModel::Entry),
+            min_fee: Some(Uint128::from(1_000u128)),
+            fee_recipients: None,
+            staking_address: None,
+            discount_tiers: None,
+            referral_share: None,
+            house_seed: None,
+            flat_band: None,
+            bucket_bands: None,
+            fixed_multiplier: None,
+            lock_buffer: None,
+            reveal_window: None,
+            reveal_penalty: None,
+            integrators: None,
+            jackpot_share: None,
+            jackpot_streak: None,
+            jackpot_min_bet: None,
+            min_subscription_bet: None,
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            update,
+        )
+        .unwrap();
+        let msg = ExecuteMsg::MakePrediction {
+            side: Outcome::Up,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
+
+        // The open round keeps the fee settings it was opened with
+        let res = execute(
+            deps.as_mut(),
+            open_env,
+            mock_info("player1", &[Coin::new(1_000, "uusd")]),
+            msg.clone(),
+        )
+        .unwrap();
+        assert!(!res.attributes.iter().any(|attr| attr.key == "fee"));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            ExecuteMsg::ResolvePrediction { timeframe: None },
+        )
+        .unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[Coin::new(1_000, "uusd")]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::BetBelowFee {});
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[Coin::new(100_000_000, "uusd")]),
+            msg,
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 0);
+        assert_eq!(
+            res.attributes,
+            vec![
+                Attribute::new("action", "make_prediction"),
+                Attribute::new("entered", "up"),
+                Attribute::new("committed", "95000000"),
+                Attribute::new("prediction_id", "3"),
+                Attribute::new("fee", "5000000")
+            ]
+        );
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.accrued_fees, Uint128::from(30_000_000u128));
+
+        // Accrued fees are split between the recipients by weight
+        let update = |fee_recipients: Vec<FeeRecipientMsg>| ExecuteMsg::UpdateConfig {
+            collector_address: None,
+            collector_ratio: None,
+            fee_model: None,
+            min_fee: None,
+            fee_recipients: Some(fee_recipients),
+            staking_address: None,
+            discount_tiers: None,
+            referral_share: None,
+            house_seed: None,
+            flat_band: None,
+            bucket_bands: None,
+            fixed_multiplier: None,
+            lock_buffer: None,
+            reveal_window: None,
+            reveal_penalty: None,
+            integrators: None,
+            jackpot_share: None,
+            jackpot_streak: None,
+            jackpot_min_bet: None,
+            min_subscription_bet: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            update(vec![FeeRecipientMsg {
+                address: "treasury".to_string(),
+                weight: 0,
+            }]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidFeeRecipients {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            update(vec![
+                FeeRecipientMsg {
+                    address: "collector".to_string(),
+                    weight: u64::MAX,
+                },
+                FeeRecipientMsg {
+                    address: "treasury".to_string(),
+                    weight: 1,
+                },
+            ]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidFeeRecipients {});
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            update(vec![
+                FeeRecipientMsg {
+                    address: "collector".to_string(),
+                    weight: 3,
+                },
+                FeeRecipientMsg {
+                    address: "treasury".to_string(),
+                    weight: 1,
+                },
+            ]),
+        )
+        .unwrap();
+        let config = query_config(deps.as_ref()).unwrap();
+        assert_eq!(config.fee_recipients.len(), 2);
+
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("anyone", &[]),
+            ExecuteMsg::DistributeFees {},
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.attributes,
+            vec![
+                Attribute::new("action", "distribute_fees"),
+                Attribute::new("collector", "22500000"),
+                Attribute::new("treasury", "7500000"),
+                Attribute::new("distributed", "30000000")
+            ]
+        );
+        let state = query_state(deps.as_ref()).unwrap();
+        assert!(state.accrued_fees.is_zero());
+    }
+
+    #[test]
+    fn proper_fee_discount() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let tier = |min_stake: u128, discount: u64| DiscountTier {
+            min_stake: Uint128::from(min_stake),
+            discount: Decimal::percent(discount),
+        };
+        let update = |discount_tiers: Vec<DiscountTier>| ExecuteMsg::UpdateConfig {
+            collector_address: None,
+            collector_ratio: None,
+            fee_model: None,
+            min_fee: None,
+            fee_recipients: None,
+            staking_address: Some("staking".to_string()),
+            discount_tiers: Some(discount_tiers),
+            referral_share: None,
+            house_seed: None,
+            flat_band: None,
+            bucket_bands: None,
+            fixed_multiplier: None,
+            lock_buffer: None,
+            reveal_window: None,
+            reveal_penalty: None,
+            integrators: None,
+            jackpot_share: None,
+            jackpot_streak: None,
+            jackpot_min_bet: None,
+            min_subscription_bet: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(vec![tier(1_000, 101)]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidFee {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(vec![tier(1_000, 10), tier(10_000, 50)]),
+        )
+        .unwrap();
+        let config = query_config(deps.as_ref()).unwrap();
+        assert_eq!(config.staking_address, Some("staking".to_string()));
+        assert_eq!(config.discount_tiers.len(), 2);
+
+        // Player1 reaches the highest tier, player2 stakes below any tier
+        deps.querier.stake("player1", Uint128::from(20_000u128));
+        deps.querier.stake("player2", Uint128::from(500u128));
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let bets = vec![
+            ("player1", Outcome::Up, 100_000_000u128),
+            ("player2", Outcome::Up, 100_000_000u128),
+            ("player3", Outcome::Down, 200_000_000u128),
+        ];
+        for (player, side, amount) in bets {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(player, &[Coin::new(amount, "uusd")]),
+                ExecuteMsg::MakePrediction {
+                    side,
+                    referrer: None,
+                    timeframe: None,
+                    amount: None,
+                },
+            )
+            .unwrap();
+        }
+
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(455_000_000u128),
+        );
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+
+        // Staking once the bets are placed does not lower the fee
+        deps.querier.stake("player2", Uint128::from(20_000u128));
+
+        // Each winner gets 200M, the 10M fee is halved for player1 only
+        env.block.time = env
+            .block
+            .time
+            .plus_seconds(config.round_time)
+            .plus_seconds(config.limit_time);
+        for player in ["player1", "player2"] {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("bot", &[]),
+                ExecuteMsg::ResolveGame {
+                    address: player.to_string(),
+                    round: vec![0],
+                },
+            )
+            .unwrap();
+        }
+        let game = query_game(deps.as_ref(), "player1".to_string(), 0).unwrap();
+        assert_eq!(game.discount, Decimal::percent(50));
+        let game = query_game(deps.as_ref(), "player2".to_string(), 0).unwrap();
+        assert_eq!(game.discount, Decimal::zero());
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.accrued_fees, Uint128::from(15_000_000u128));
+        let stats = query_user_stats(deps.as_ref(), "player1".to_string()).unwrap();
+        assert_eq!(stats.payout, Uint128::from(195_000_000u128));
+    }
+
+    #[test]
+    fn proper_referral() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let update = |fee_model: Option<FeeModel>| ExecuteMsg::UpdateConfig {
+            collector_address: None,
+            collector_ratio: None,
+            fee_model,
+            min_fee: None,
+            fee_recipients: None,
+            staking_address: None,
+            discount_tiers: None,
+            referral_share: Some(Decimal::percent(20)),
+            house_seed: None,
+            flat_band: None,
+            bucket_bands: None,
+            fixed_multiplier: None,
+            lock_buffer: None,
+            reveal_window: None,
+            reveal_penalty: None,
+            integrators: None,
+            jackpot_share: None,
+            jackpot_streak: None,
+            jackpot_min_bet: None,
+            min_subscription_bet: None,
+        };
+        // Referral rewards are only taken from the winnings fee
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(Some(FeeModel::Entry)),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ReferralWithoutWinningsFee {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(None),
+        )
+        .unwrap();
+
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let bet = |side: Outcome, referrer: &str| ExecuteMsg::MakePrediction {
+            side,
+            referrer: Some(referrer.to_string()),
+            timeframe: None,
+            amount: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(100_000_000, "uusd")]),
+            bet(Outcome::Up, "player1"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidReferrer {});
+
+        // Only the first referrer of player1 is recorded
+        let bets = vec![
+            ("player1", Outcome::Up, "player2"),
+            ("player1", Outcome::Up, "player3"),
+            ("player3", Outcome::Down, "player2"),
+        ];
+        for (player, side, referrer) in bets {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(player, &[Coin::new(50_000_000, "uusd")]),
+                bet(side, referrer),
+            )
+            .unwrap();
+        }
+        let referral = query_referral_stats(deps.as_ref(), "player2".to_string()).unwrap();
+        assert_eq!(referral.referred_users, 2);
+        let referral = query_referral_stats(deps.as_ref(), "player3".to_string()).unwrap();
+        assert_eq!(referral.referred_users, 0);
+
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(455_000_000u128),
+        );
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+
+        // Player1 wins 150M, 20% of the 7.5M fee goes to player2
+        env.block.time = env
+            .block
+            .time
+            .plus_seconds(config.round_time)
+            .plus_seconds(config.limit_time);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            ExecuteMsg::ResolveGame {
+                address: "player1".to_string(),
+                round: vec![0],
+            },
+        )
+        .unwrap();
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.accrued_fees, Uint128::from(6_000_000u128));
+        let referral = query_referral_stats(deps.as_ref(), "player2".to_string()).unwrap();
+        assert_eq!(referral.total_rewards, Uint128::from(1_500_000u128));
+        assert_eq!(referral.pending_rewards, Uint128::from(1_500_000u128));
+
+        let msg = ExecuteMsg::ClaimReferralRewards {};
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player2", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.attributes,
+            vec![
+                Attribute::new("action", "claim_referral_rewards"),
+                Attribute::new("recipient", "player2"),
+                Attribute::new("amount", "1500000")
+            ]
+        );
+        let referral = query_referral_stats(deps.as_ref(), "player2".to_string()).unwrap();
+        assert_eq!(referral.total_rewards, Uint128::from(1_500_000u128));
+        assert!(referral.pending_rewards.is_zero());
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.liabilities, state.accrued_fees);
+
+        let err = execute(deps.as_mut(), env, mock_info("player2", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::EmptyFunds {});
+    }
+
+    #[test]
+    fn proper_house() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let deposit = ExecuteMsg::DepositHouse {};
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(500_000_000, "uusd")]),
+            deposit.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[Coin::new(500_000_000, "uusd")]),
+            deposit,
+        )
+        .unwrap();
+        let update = ExecuteMsg::UpdateConfig {
+            collector_address: None,
+            collector_ratio: None,
+            fee_model: None,
+            min_fee: None,
+            fee_recipients: None,
+            staking_address: None,
+            discount_tiers: None,
+            referral_share: None,
+            house_seed: Some(Uint128::from(100_000_000u128)),
+            flat_band: None,
+            bucket_bands: None,
+            fixed_multiplier: None,
+            lock_buffer: None,
+            reveal_window: None,
+            reveal_penalty: None,
+            integrators: None,
+            jackpot_share: None,
+            jackpot_streak: None,
+            jackpot_min_bet: None,
+            min_subscription_bet: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), update).unwrap();
+
+        // Only one side is played, the house seeds the other one when locking
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(100_000_000, "uusd")]),
+            ExecuteMsg::MakePrediction {
+                side: Outcome::Up,
+                referrer: None,
+                timeframe: None,
+                amount: None,
+            },
+        )
+        .unwrap();
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let prediction = query_prediction(deps.as_ref(), env.clone(), 0).unwrap();
+        assert_eq!(prediction.down, Uint128::from(100_000_000u128));
+        assert_eq!(prediction.house_down, Uint128::from(100_000_000u128));
+        let house = query_house(deps.as_ref()).unwrap();
+        assert_eq!(house.balance, Uint128::from(400_000_000u128));
+        assert_eq!(house.in_play, Uint128::from(100_000_000u128));
+
+        // Down wins and the house takes the whole pool
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(655_000_000u128),
+        );
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+        let prediction = query_prediction(deps.as_ref(), env.clone(), 0).unwrap();
+        assert!(prediction.success);
+        assert_eq!(prediction.claimed, Uint128::from(200_000_000u128));
+        let house = query_house(deps.as_ref()).unwrap();
+        assert_eq!(
+            house,
+            HouseResponse {
+                balance: Uint128::from(600_000_000u128),
+                in_play: Uint128::zero(),
+                total_deposited: Uint128::from(500_000_000u128),
+                total_withdrawn: Uint128::zero(),
+                total_won: Uint128::from(100_000_000u128),
+                total_lost: Uint128::zero(),
+            }
+        );
+
+        let withdraw = |amount: u128| ExecuteMsg::WithdrawHouse {
+            amount: Uint128::from(amount),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            withdraw(600_000_001),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientHouseBalance {});
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("creator", &[]),
+            withdraw(600_000_000),
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+        let state = query_state(deps.as_ref()).unwrap();
+        assert!(state.liabilities.is_zero());
+    }
+
+    #[test]
+    fn proper_flat_outcome() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let update = |flat_band: Decimal| ExecuteMsg::UpdateConfig {
+            collector_address: None,
+            collector_ratio: None,
+            fee_model: None,
+            min_fee: None,
+            fee_recipients: None,
+            staking_address: None,
+            discount_tiers: None,
+            referral_share: None,
+            house_seed: None,
+            flat_band: Some(flat_band),
+            bucket_bands: None,
+            fixed_multiplier: None,
+            lock_buffer: None,
+            reveal_window: None,
+            reveal_penalty: None,
+            integrators: None,
+            jackpot_share: None,
+            jackpot_streak: None,
+            jackpot_min_bet: None,
+            min_subscription_bet: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(Decimal::one()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidFlatBand {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(Decimal::percent(1)),
+        )
+        .unwrap();
+
+        // The band applies from the next round opened
+        let bet = |side: Outcome| ExecuteMsg::MakePrediction {
+            side,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player2", &[Coin::new(100_000_000, "uusd")]),
+            bet(Outcome::Flat),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::FlatDisabled {});
+
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+
+        let bets = vec![
+            ("player1", Outcome::Up),
+            ("player2", Outcome::Flat),
+            ("player3", Outcome::Down),
+        ];
+        for (player, side) in bets {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(player, &[Coin::new(100_000_000, "uusd")]),
+                bet(side),
+            )
+            .unwrap();
+        }
+        let round = query_current_round(deps.as_ref(), env.clone(), None).unwrap();
+        assert_eq!(round.flat, Uint128::from(100_000_000u128));
+        assert_eq!(round.total, Uint128::from(300_000_000u128));
+        assert_eq!(round.open_round.flat_band, Decimal::percent(1));
+
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+
+        // A move of 0.9% stays within the band
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(550_000_000u128),
+        );
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("resolved", "flat")));
+        let prediction = query_prediction(deps.as_ref(), env.clone(), 1).unwrap();
+        assert!(prediction.success);
+        assert!(prediction.is_flat);
+        assert_eq!(prediction.is_up, None);
+
+        env.block.time = env
+            .block
+            .time
+            .plus_seconds(config.round_time)
+            .plus_seconds(config.limit_time);
+        for player in ["player1", "player2"] {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("bot", &[]),
+                ExecuteMsg::ResolveGame {
+                    address: player.to_string(),
+                    round: vec![1],
+                },
+            )
+            .unwrap();
+        }
+        let game = query_game(deps.as_ref(), "player2".to_string(), 1).unwrap();
+        assert_eq!(game.prize, Uint128::from(300_000_000u128));
+        let game = query_game(deps.as_ref(), "player1".to_string(), 1).unwrap();
+        assert!(game.prize.is_zero());
+        let stats = query_user_stats(deps.as_ref(), "player2".to_string()).unwrap();
+        assert_eq!(stats.payout, Uint128::from(285_000_000u128));
+    }
+
+    #[test]
+    fn proper_bucket_market() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let update = |bucket_bands: Vec<Decimal>| ExecuteMsg::UpdateConfig {
+            collector_address: None,
+            collector_ratio: None,
+            fee_model: None,
+            min_fee: None,
+            fee_recipients: None,
+            staking_address: None,
+            discount_tiers: None,
+            referral_share: None,
+            house_seed: None,
+            flat_band: None,
+            bucket_bands: Some(bucket_bands),
+            fixed_multiplier: None,
+            lock_buffer: None,
+            reveal_window: None,
+            reveal_penalty: None,
+            integrators: None,
+            jackpot_share: None,
+            jackpot_streak: None,
+            jackpot_min_bet: None,
+            min_subscription_bet: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(vec![Decimal::percent(2), Decimal::percent(1)]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidBuckets {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(vec![Decimal::percent(1), Decimal::percent(2)]),
+        )
+        .unwrap();
+
+        // Buckets open with the next round
+        let bet = |bucket: u32| ExecuteMsg::MakeBucketPrediction {
+            bucket,
+            referrer: None,
+            timeframe: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(100_000_000, "uusd")]),
+            bet(0),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidBucket {});
+
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[Coin::new(100_000_000, "uusd")]),
+            bet(5),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidBucket {});
+        let bets = vec![("player1", 3), ("player2", 2), ("player3", 4)];
+        for (player, bucket) in bets {
+            let res = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(player, &[Coin::new(100_000_000, "uusd")]),
+                bet(bucket),
+            )
+            .unwrap();
+            assert!(res
+                .attributes
+                .contains(&Attribute::new("entered", format!("bucket_{}", bucket))));
+        }
+        let prediction = query_prediction(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(prediction.buckets.len(), 5);
+        assert_eq!(prediction.buckets[3], Uint128::from(100_000_000u128));
+
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+
+        // A rise of 1.46% falls between the first and second bands
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(547_000_000u128),
+        );
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+        assert!(res
+            .attributes
+            .contains(&Attribute::new("resolved_bucket", "3")));
+        let prediction = query_prediction(deps.as_ref(), env.clone(), 1).unwrap();
+        assert!(!prediction.success);
+        assert_eq!(prediction.bucket, Some(3));
+        assert_eq!(prediction.status, PredictionStatus::Resolved);
+        // Only the bucket market paid, the round still counts as succeeded
+        let stats = query_stats(deps.as_ref()).unwrap();
+        assert_eq!(stats.rounds_succeeded, 1);
+        assert_eq!(stats.rounds_voided, 1);
+
+        env.block.time = env
+            .block
+            .time
+            .plus_seconds(config.round_time)
+            .plus_seconds(config.limit_time);
+        for player in ["player1", "player2"] {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("bot", &[]),
+                ExecuteMsg::ResolveGame {
+                    address: player.to_string(),
+                    round: vec![1],
+                },
+            )
+            .unwrap();
+        }
+        let game = query_game(deps.as_ref(), "player1".to_string(), 1).unwrap();
+        assert_eq!(game.prize, Uint128::from(300_000_000u128));
+        let stats = query_user_stats(deps.as_ref(), "player1".to_string()).unwrap();
+        assert_eq!(stats.payout, Uint128::from(285_000_000u128));
+        assert_eq!(stats.rounds_won, 1);
+        let stats = query_user_stats(deps.as_ref(), "player2".to_string()).unwrap();
+        assert_eq!(stats.rounds_played, 1);
+        assert!(stats.payout.is_zero());
+    }
+
+    #[test]
+    fn proper_fixed_odds() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[Coin::new(100_000_000, "uusd")]),
+            ExecuteMsg::DepositHouse {},
+        )
+        .unwrap();
+        let update = |fixed_multiplier: Decimal| ExecuteMsg::UpdateConfig {
+            collector_address: None,
+            collector_ratio: None,
+            fee_model: None,
+            min_fee: None,
+            fee_recipients: None,
+            staking_address: None,
+            discount_tiers: None,
+            referral_share: None,
+            house_seed: None,
+            flat_band: None,
+            bucket_bands: None,
+            fixed_multiplier: Some(fixed_multiplier),
+            lock_buffer: None,
+            reveal_window: None,
+            reveal_penalty: None,
+            integrators: None,
+            jackpot_share: None,
+            jackpot_streak: None,
+            jackpot_min_bet: None,
+            min_subscription_bet: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(Decimal::one()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidMultiplier {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(Decimal::permille(1900)),
+        )
+        .unwrap();
+
+        // The multiplier applies from the next round
+        let bet = |up: bool| ExecuteMsg::MakeFixedOddsPrediction {
+            up,
+            referrer: None,
+            timeframe: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(100_000_000, "uusd")]),
+            bet(true),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::FixedOddsDisabled {});
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+
+        // Each bet reserves its potential profit from the house
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[Coin::new(100_000_000, "uusd")]),
+            bet(true),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player2", &[Coin::new(20_000_000, "uusd")]),
+            bet(false),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ExposureExceeded {});
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player2", &[Coin::new(10_000_000, "uusd")]),
+            bet(false),
+        )
+        .unwrap();
+        let prediction = query_prediction(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(prediction.fixed_up, Uint128::from(100_000_000u128));
+        assert_eq!(prediction.fixed_reserved_up, Uint128::from(90_000_000u128));
+        assert_eq!(prediction.fixed_reserved_down, Uint128::from(9_000_000u128));
+        let house = query_house(deps.as_ref()).unwrap();
+        assert_eq!(house.balance, Uint128::from(1_000_000u128));
+        assert_eq!(house.in_play, Uint128::from(99_000_000u128));
+
+        // Up wins, the house keeps the down stake and pays the up reserve
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(455_000_000u128),
+        );
+        env.block.time = env.block.time.plus_seconds(config.round_time);
+        execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+        let prediction = query_prediction(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(prediction.fixed_outcome, Some(Outcome::Up));
+        let house = query_house(deps.as_ref()).unwrap();
+        assert_eq!(house.balance, Uint128::from(20_000_000u128));
+        assert!(house.in_play.is_zero());
+        assert_eq!(house.total_won, Uint128::from(10_000_000u128));
+        assert_eq!(house.total_lost, Uint128::from(90_000_000u128));
+
+        env.block.time = env
+            .block
+            .time
+            .plus_seconds(config.round_time + config.limit_time);
+        for player in ["player1", "player2"] {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("bot", &[]),
+                ExecuteMsg::ResolveGame {
+                    address: player.to_string(),
+                    round: vec![1],
+                },
+            )
+            .unwrap();
+        }
+        let game = query_game(deps.as_ref(), "player1".to_string(), 1).unwrap();
+        assert_eq!(game.multiplier, Decimal::permille(1900));
+        assert_eq!(game.prize, Uint128::from(190_000_000u128));
+        let stats = query_user_stats(deps.as_ref(), "player1".to_string()).unwrap();
+        assert_eq!(stats.payout, Uint128::from(190_000_000u128));
+        assert_eq!(stats.rounds_won, 1);
+        let game = query_game(deps.as_ref(), "player2".to_string(), 1).unwrap();
+        assert!(game.prize.is_zero());
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.liabilities, Uint128::from(20_000_000u128));
+    }
+
+    #[test]
+    fn proper_timeframes() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 2,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let add = |round_time: u64| ExecuteMsg::AddTimeframe {
+            round_time,
+            limit_time: 60,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[]),
+            add(3_600),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err =
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), add(0)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidTimeframe {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            add(3_600),
+        )
+        .unwrap();
+        let timeframes = query_timeframes(deps.as_ref()).unwrap().timeframes;
+        assert_eq!(timeframes.len(), 2);
+        assert_eq!(
+            timeframes[1],
+            TimeframeResponse {
+                timeframe: 1,
+                round_time: 3_600,
+                limit_time: 60,
+                open_round: 1,
+                locked_round: None,
+            }
+        );
+
+        let bet = |timeframe: Option<u32>| ExecuteMsg::MakePrediction {
+            side: Outcome::Up,
+            referrer: None,
+            timeframe,
+            amount: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(100_000_000, "uusd")]),
+            bet(Some(2)),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidTimeframe {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(100_000_000, "uusd")]),
+            bet(Some(1)),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player2", &[Coin::new(50_000_000, "uusd")]),
+            bet(None),
+        )
+        .unwrap();
+        assert_eq!(
+            query_prediction(deps.as_ref(), mock_env(), 1).unwrap().up,
+            Uint128::from(100_000_000u128)
+        );
+        assert_eq!(
+            query_prediction(deps.as_ref(), mock_env(), 0).unwrap().up,
+            Uint128::from(50_000_000u128)
+        );
+
+        // The default timeframe rolls over without touching the hourly one
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(300);
+        let resolve = |timeframe: Option<u32>| ExecuteMsg::ResolvePrediction { timeframe };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            resolve(None),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            resolve(Some(1)),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PredictionStillInProgress {});
+        let current = query_current_round(deps.as_ref(), env.clone(), None).unwrap();
+        assert_eq!(current.open_round.round, 2);
+        assert_eq!(current.locked_round.unwrap().round, 0);
+        let current = query_current_round(deps.as_ref(), env.clone(), Some(1)).unwrap();
+        assert_eq!(current.open_round.round, 1);
+        assert_eq!(current.open_round.status, PredictionStatus::Open);
+        assert!(current.locked_round.is_none());
+
+        // Rounds of other timeframes do not shorten the leaderboard epochs
+        let epoch = |env: Env| {
+            query_leaderboard(
+                deps.as_ref(),
+                env,
+                LeaderboardKind::Volume,
+                LeaderboardPeriod::Epoch { epoch: None },
+                None,
+            )
+            .unwrap()
+            .epoch
+        };
+        assert_eq!(epoch(env.clone()), Some(0));
+        let mut later = env.clone();
+        later.block.time = later.block.time.plus_seconds(600);
+        assert_eq!(epoch(later), Some(1));
+
+        env.block.time = env.block.time.plus_seconds(3_300);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            resolve(Some(1)),
+        )
+        .unwrap();
+        let current = query_current_round(deps.as_ref(), env.clone(), Some(1)).unwrap();
+        assert_eq!(current.open_round.round, 3);
+        assert_eq!(current.open_round.timeframe, 1);
+        assert_eq!(
+            current.open_round.closing_time,
+            env.block.time.plus_seconds(3_600).seconds()
+        );
+        let locked = current.locked_round.unwrap();
+        assert_eq!(locked.round, 1);
+        assert_eq!(locked.status, PredictionStatus::Locked);
+        assert_eq!(locked.locked_price, Uint128::from(27_477_477u128));
+
+        // Rounds are listed by timeframe
+        let rounds = |timeframe: Option<u32>| {
+            query_predictions(
+                deps.as_ref(),
+                env.clone(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                timeframe,
+            )
+            .unwrap()
+            .predictions
+            .into_iter()
+            .map(|prediction| prediction.round)
+            .collect::<Vec<u64>>()
+        };
+        assert_eq!(rounds(None), vec![0, 1, 2, 3]);
+        assert_eq!(rounds(Some(0)), vec![0, 2]);
+        assert_eq!(rounds(Some(1)), vec![1, 3]);
+    }
+
+    #[test]
+    fn proper_lock_buffer() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let update = |lock_buffer: u64| ExecuteMsg::UpdateConfig {
+            collector_address: None,
+            collector_ratio: None,
+            fee_model: None,
+            min_fee: None,
+            fee_recipients: None,
+            staking_address: None,
+            discount_tiers: None,
+            referral_share: None,
+            house_seed: None,
+            flat_band: None,
+            bucket_bands: None,
+            fixed_multiplier: None,
+            lock_buffer: Some(lock_buffer),
+            reveal_window: None,
+            reveal_penalty: None,
+            integrators: None,
+            jackpot_share: None,
+            jackpot_streak: None,
+            jackpot_min_bet: None,
+            min_subscription_bet: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(300),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidLockBuffer {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(30),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::AddTimeframe {
+                round_time: 30,
+                limit_time: 30,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidTimeframe {});
+
+        // Betting closes 30 seconds before the closing time
+        let bet = ExecuteMsg::MakePrediction {
+            side: Outcome::Up,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(269);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[Coin::new(100_000_000, "uusd")]),
+            bet.clone(),
+        )
+        .unwrap();
+        env.block.time = env.block.time.plus_seconds(1);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[Coin::new(100_000_000, "uusd")]),
+            bet.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::BettingClosed {});
+        let current = query_current_round(deps.as_ref(), env.clone(), None).unwrap();
+        assert!(!current.betting_open);
+        assert_eq!(current.seconds_until_lock, 30);
+
+        // A round past its closing time stays closed until it is resolved
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(0),
+        )
+        .unwrap();
+        env.block.time = env.block.time.plus_seconds(45);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[Coin::new(100_000_000, "uusd")]),
+            bet.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::BettingClosed {});
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            ExecuteMsg::ResolvePrediction { timeframe: None },
+        )
+        .unwrap();
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("player1", &[Coin::new(100_000_000, "uusd")]),
+            bet,
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .contains(&Attribute::new("prediction_id", "1")));
+    }
+
+    #[test]
+    fn proper_commit_reveal() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let update = |reveal_window: u64| ExecuteMsg::UpdateConfig {
+            collector_address: None,
+            collector_ratio: None,
+            fee_model: None,
+            min_fee: None,
+            fee_recipients: None,
+            staking_address: None,
+            discount_tiers: None,
+            referral_share: None,
+            house_seed: None,
+            flat_band: None,
+            bucket_bands: None,
+            fixed_multiplier: None,
+            lock_buffer: None,
+            reveal_window: Some(reveal_window),
+            reveal_penalty: Some(Decimal::percent(10)),
+            integrators: None,
+            jackpot_share: Some(Decimal::percent(20)),
+            jackpot_streak: None,
+            jackpot_min_bet: None,
+            min_subscription_bet: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(300),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidLockBuffer {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update(60),
+        )
+        .unwrap();
+
+        let commit = |sender: &str, up: bool, salt: &str| ExecuteMsg::CommitPrediction {
+            hash: Binary::from(
+                Sha256::digest(
+                    format!("{}:{}:{}", sender, if up { "up" } else { "down" }, salt).as_bytes(),
+                )
+                .as_slice(),
+            ),
+            referrer: None,
+            timeframe: None,
+        };
+        let reveal = |up: bool, salt: &str| ExecuteMsg::RevealPrediction {
+            up,
+            salt: salt.to_string(),
+            timeframe: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(100_000_000, "uusd")]),
+            commit("player1", true, "salt1"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::CommitRevealDisabled {});
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(300);
+        let resolve = ExecuteMsg::ResolvePrediction { timeframe: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            resolve.clone(),
+        )
+        .unwrap();
+
+        // Commitments are taken until the reveal window opens
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[Coin::new(100_000_000, "uusd")]),
+            commit("player1", true, "salt1"),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player2", &[Coin::new(50_000_000, "uusd")]),
+            commit("player2", false, "salt2"),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player2", &[Coin::new(50_000_000, "uusd")]),
+            commit("player2", true, "salt3"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AlreadyCommitted {});
+        // A commitment copied from another address cannot be revealed
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player4", &[Coin::new(10_000_000, "uusd")]),
+            commit("player1", true, "salt1"),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player3", &[Coin::new(100_000_000, "uusd")]),
+            ExecuteMsg::MakePrediction {
+                side: Outcome::Down,
+                referrer: None,
+                timeframe: None,
+                amount: None,
+            },
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[]),
+            reveal(true, "salt1"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::RevealClosed {});
+        let prediction = query_prediction(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(prediction.committed, Uint128::from(160_000_000u128));
+        assert_eq!(prediction.down, Uint128::from(100_000_000u128));
+
+        env.block.time = env.block.time.plus_seconds(250);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player4", &[Coin::new(100_000_000, "uusd")]),
+            commit("player4", true, "salt4"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::BettingClosed {});
+        // Sides already revealed cannot be followed by new bets
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player5", &[Coin::new(100_000_000, "uusd")]),
+            ExecuteMsg::MakePrediction {
+                side: Outcome::Up,
+                referrer: None,
+                timeframe: None,
+                amount: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::BettingClosed {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player4", &[]),
+            reveal(true, "salt1"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidReveal {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[]),
+            reveal(true, "salt2"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidReveal {});
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[]),
+            reveal(true, "salt1"),
+        )
+        .unwrap();
+        let prediction = query_prediction(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(prediction.up, Uint128::from(100_000_000u128));
+        assert_eq!(prediction.committed, Uint128::from(60_000_000u128));
+
+        // Up wins, the unrevealed commitment is refunded minus the penalty
+        env.block.time = env.block.time.plus_seconds(50);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            resolve.clone(),
+        )
+        .unwrap();
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(455_000_000u128),
+        );
+        env.block.time = env.block.time.plus_seconds(300);
+        execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), resolve).unwrap();
+        env.block.time = env.block.time.plus_seconds(330);
+        for player in ["player1", "player2"] {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("bot", &[]),
+                ExecuteMsg::ResolveGame {
+                    address: player.to_string(),
+                    round: vec![1],
+                },
+            )
+            .unwrap();
+        }
+        let game = query_game(deps.as_ref(), "player1".to_string(), 1).unwrap();
+        assert_eq!(game.prize, Uint128::from(200_000_000u128));
+        let game = query_game(deps.as_ref(), "player2".to_string(), 1).unwrap();
+        assert_eq!(game.committed, Uint128::from(50_000_000u128));
+        assert_eq!(game.prize, Uint128::from(45_000_000u128));
+        let stats = query_user_stats(deps.as_ref(), "player2".to_string()).unwrap();
+        assert_eq!(stats.rounds_refunded, 1);
+
+        // The fee and the penalty both fund the jackpot
+        let jackpot = query_jackpot(deps.as_ref()).unwrap();
+        assert_eq!(jackpot.balance, Uint128::from(3_000_000u128));
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.accrued_fees, Uint128::from(12_000_000u128));
+    }
+
+    #[test]
+    fn proper_subscriptions() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::UpdateConfig {
+                collector_address: None,
+                collector_ratio: None,
+                fee_model: None,
+                min_fee: None,
+                fee_recipients: None,
+                staking_address: None,
+                discount_tiers: None,
+                referral_share: None,
+                house_seed: None,
+                flat_band: None,
+                bucket_bands: None,
+                fixed_multiplier: None,
+                lock_buffer: None,
+                reveal_window: None,
+                reveal_penalty: None,
+                integrators: None,
+                jackpot_share: None,
+                jackpot_streak: None,
+                jackpot_min_bet: None,
+                min_subscription_bet: Some(Uint128::zero()),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidMinSubscriptionBet {});
+
+        let subscribe = ExecuteMsg::Subscribe {
+            side: Outcome::Up,
+            amount_per_round: Uint128::from(10_000_000u128),
+            rounds: 3,
+            timeframe: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(29_000_000, "uusd")]),
+            subscribe.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidSubscription {});
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(2_997, "uusd")]),
+            ExecuteMsg::Subscribe {
+                side: Outcome::Up,
+                amount_per_round: Uint128::from(999u128),
+                rounds: 3,
+                timeframe: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidSubscription {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(30_000_000, "uusd")]),
+            subscribe.clone(),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(30_000_000, "uusd")]),
+            subscribe,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AlreadySubscribed {});
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.liabilities, Uint128::from(30_000_000u128));
+
+        // Flat bets are refused while the flat band is disabled
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player2", &[Coin::new(10_000_000, "uusd")]),
+            ExecuteMsg::Subscribe {
+                side: Outcome::Flat,
+                amount_per_round: Uint128::from(5_000_000u128),
+                rounds: 2,
+                timeframe: None,
+            },
+        )
+        .unwrap();
+
+        // Each new round receives a bet from the escrow
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let mut env = mock_env();
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        for round in 1..3u64 {
+            env.block.time = env.block.time.plus_seconds(300);
+            let res = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("bot", &[]),
+                msg.clone(),
+            )
+            .unwrap();
+            assert!(res
+                .attributes
+                .contains(&Attribute::new("subscription_bets", "1")));
+            // The refused subscription is dropped and its escrow refunded
+            if round == 1 {
+                assert_eq!(
+                    res.messages,
+                    vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                        to_address: "player2".to_string(),
+                        amount: vec![Coin::new(9_900_990, "uusd")],
+                    }))]
+                );
+                assert!(res
+                    .attributes
+                    .contains(&Attribute::new("subscriptions_refunded", "10000000")));
+                assert!(query_subscription(deps.as_ref(), "player2".to_string(), None).is_err());
+            }
+            let game = query_game(deps.as_ref(), "player1".to_string(), round).unwrap();
+            assert_eq!(game.up, Uint128::from(10_000_000u128));
+        }
+        let subscription = query_subscription(deps.as_ref(), "player1".to_string(), None).unwrap();
+        assert_eq!(subscription.rounds_left, 1);
+        assert_eq!(subscription.balance, Uint128::from(10_000_000u128));
+        let stats = query_stats(deps.as_ref()).unwrap();
+        assert_eq!(stats.total_volume, Uint128::from(20_000_000u128));
+        assert_eq!(stats.unique_players, 1);
+
+        // Cancelling refunds the escrow not bet yet
+        let cancel = ExecuteMsg::CancelSubscription { timeframe: None };
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[]),
+            cancel.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "player1".to_string(),
+                amount: vec![Coin::new(9_900_990, "uusd")],
+            }))]
+        );
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.liabilities, Uint128::from(20_000_000u128));
+        let err = execute(deps.as_mut(), env, mock_info("player1", &[]), cancel).unwrap_err();
+        assert_eq!(err, ContractError::SubscriptionNotFound {});
+    }
+
+    #[test]
+    fn proper_auto_compound() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[]),
+            ExecuteMsg::SetAutoCompound {
+                enabled: true,
+                side: None,
+                timeframe: Some(1),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidTimeframe {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[]),
+            ExecuteMsg::SetAutoCompound {
+                enabled: true,
+                side: None,
+                timeframe: None,
+            },
+        )
+        .unwrap();
+
+        for (player, side) in [("player1", Outcome::Up), ("player2", Outcome::Down)] {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(player, &[Coin::new(100_000_000, "uusd")]),
+                ExecuteMsg::MakePrediction {
+                    side,
+                    referrer: None,
+                    timeframe: None,
+                    amount: None,
+                },
+            )
+            .unwrap();
+        }
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(300);
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(455_000_000u128),
+        );
+        env.block.time = env.block.time.plus_seconds(300);
+        execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+
+        // The claim is bet again on the same side in the open round instead of being sent
+        env.block.time = env.block.time.plus_seconds(30);
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            ExecuteMsg::ResolveGame {
+                address: "player1".to_string(),
+                round: vec![0],
+            },
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        assert!(res
+            .attributes
+            .contains(&Attribute::new("compounded", "190000000")));
+        let game = query_game(deps.as_ref(), "player1".to_string(), 2).unwrap();
+        assert_eq!(game.up, Uint128::from(190_000_000u128));
+        let balance = query_balance(deps.as_ref(), "player1".to_string()).unwrap();
+        assert_eq!(
+            balance,
+            BalanceResponse {
+                address: "player1".to_string(),
+                balance: Uint128::zero(),
+                auto_compound: true,
+                timeframe: Some(0),
+                side: None,
+            }
+        );
+        // The compounded stake and the accrued fee are still owed
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.liabilities, Uint128::from(200_000_000u128));
+        // No balance is opened when the whole claim is bet
+        let raw_player1 = deps.api.addr_canonicalize("player1").unwrap();
+        assert!(BALANCES
+            .may_load(deps.as_ref().storage, raw_player1.as_slice())
+            .unwrap()
+            .is_none());
+
+        // Nothing is compounded without the opt-in
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            ExecuteMsg::ResolveGame {
+                address: "player2".to_string(),
+                round: vec![0],
+            },
+        )
+        .unwrap();
+        assert!(!res
+            .attributes
+            .iter()
+            .any(|attribute| attribute.key == "compounded"));
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[]),
+            ExecuteMsg::SetAutoCompound {
+                enabled: false,
+                side: None,
+                timeframe: None,
+            },
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        let balance = query_balance(deps.as_ref(), "player1".to_string()).unwrap();
+        assert!(!balance.auto_compound);
+
+        // Once opted out the next claim is sent again, here the refund of a round bet alone
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        for _ in 0..2 {
+            env.block.time = env.block.time.plus_seconds(300);
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("bot", &[]),
+                msg.clone(),
+            )
+            .unwrap();
+        }
+        env.block.time = env.block.time.plus_seconds(30);
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("bot", &[]),
+            ExecuteMsg::ResolveGame {
+                address: "player1".to_string(),
+                round: vec![2],
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "player1".to_string(),
+                amount: vec![Coin::new(189_000_000, "uusd")],
+            }))]
+        );
+    }
+
+    #[test]
+    fn proper_internal_balance() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[]),
+            ExecuteMsg::Withdraw {
+                amount: Uint128::from(1u128),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientBalance {});
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(150_000_000, "uusd")]),
+            ExecuteMsg::Deposit {},
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .contains(&Attribute::new("balance", "150000000")));
+
+        // Without funds attached the stake is spent from the balance
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[]),
+            ExecuteMsg::MakePrediction {
+                side: Outcome::Up,
+                referrer: None,
+                timeframe: None,
+                amount: Some(Uint128::from(200_000_000u128)),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientBalance {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[]),
+            ExecuteMsg::MakePrediction {
+                side: Outcome::Up,
+                referrer: None,
+                timeframe: None,
+                amount: Some(Uint128::from(100_000_000u128)),
+            },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player2", &[Coin::new(100_000_000, "uusd")]),
+            ExecuteMsg::MakePrediction {
+                side: Outcome::Down,
+                referrer: None,
+                timeframe: None,
+                amount: None,
+            },
+        )
+        .unwrap();
+        let game = query_game(deps.as_ref(), "player1".to_string(), 0).unwrap();
+        assert_eq!(game.up, Uint128::from(100_000_000u128));
+        let balance = query_balance(deps.as_ref(), "player1".to_string()).unwrap();
+        assert_eq!(balance.balance, Uint128::from(50_000_000u128));
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.liabilities, Uint128::from(250_000_000u128));
+
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(300);
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(455_000_000u128),
+        );
+        env.block.time = env.block.time.plus_seconds(300);
+        execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+
+        // The claim is credited to the balance instead of being sent
+        env.block.time = env.block.time.plus_seconds(30);
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            ExecuteMsg::ResolveGame {
+                address: "player1".to_string(),
+                round: vec![0],
+            },
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        assert!(res
+            .attributes
+            .contains(&Attribute::new("credited", "190000000")));
+        let balance = query_balance(deps.as_ref(), "player1".to_string()).unwrap();
+        assert_eq!(balance.balance, Uint128::from(240_000_000u128));
+
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("player1", &[]),
+            ExecuteMsg::Withdraw {
+                amount: Uint128::from(240_000_000u128),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "player1".to_string(),
+                amount: vec![Coin::new(239_000_000, "uusd")],
+            }))]
+        );
+        // Emptying the balance closes it so later claims are sent again
+        let raw_player1 = deps.api.addr_canonicalize("player1").unwrap();
+        assert!(BALANCES
+            .may_load(deps.as_ref().storage, raw_player1.as_slice())
+            .unwrap()
+            .is_none());
+        // Only the accrued fee is still owed
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.liabilities, Uint128::from(10_000_000u128));
+    }
+
+    #[test]
+    fn proper_make_prediction_for() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::MakePredictionFor {
+            beneficiary: "player1".to_string(),
+            side: Outcome::Up,
+            timeframe: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("loterra", &[Coin::new(100_000_000, "uusd")]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::UpdateConfig {
+                collector_address: None,
+                collector_ratio: None,
+                fee_model: None,
+                min_fee: None,
+                fee_recipients: None,
+                staking_address: None,
+                discount_tiers: None,
+                referral_share: None,
+                house_seed: None,
+                flat_band: None,
+                bucket_bands: None,
+                fixed_multiplier: None,
+                lock_buffer: None,
+                reveal_window: None,
+                reveal_penalty: None,
+                integrators: Some(vec!["loterra".to_string()]),
+                jackpot_share: None,
+                jackpot_streak: None,
+                jackpot_min_bet: None,
+                min_subscription_bet: None,
+            },
+        )
+        .unwrap();
+        let config = query_config(deps.as_ref()).unwrap();
+        assert_eq!(config.integrators, vec!["loterra".to_string()]);
+
+        // The game belongs to the beneficiary while the integrator pays
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("loterra", &[Coin::new(100_000_000, "uusd")]),
+            msg,
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .contains(&Attribute::new("beneficiary", "player1")));
+        assert!(res
+            .attributes
+            .contains(&Attribute::new("integrator", "loterra")));
+        let game = query_game(deps.as_ref(), "player1".to_string(), 0).unwrap();
+        assert_eq!(game.up, Uint128::from(100_000_000u128));
+        assert!(query_game(deps.as_ref(), "loterra".to_string(), 0).is_err());
+
+        // Flat bets go through the same checks as MakePrediction
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("loterra", &[Coin::new(100_000_000, "uusd")]),
+            ExecuteMsg::MakePredictionFor {
+                beneficiary: "player2".to_string(),
+                side: Outcome::Flat,
+                timeframe: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::FlatDisabled {});
+    }
+
+    #[test]
+    fn proper_tournament() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let create = |winners: u32| ExecuteMsg::CreateTournament {
+            timeframe: None,
+            start_round: 0,
+            end_round: 0,
+            entry_fee: Uint128::from(10_000_000u128),
+            starting_chips: Uint128::from(1_000u128),
+            winners,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[]),
+            create(1),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            create(0),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidTournament {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            create(1),
+        )
+        .unwrap();
+
+        let enter = ExecuteMsg::EnterTournament { tournament_id: 0 };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player3", &[Coin::new(5_000_000, "uusd")]),
+            enter.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidEntryFee {});
+        for player in ["player1", "player2"] {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(player, &[Coin::new(10_000_000, "uusd")]),
+                enter.clone(),
+            )
+            .unwrap();
+        }
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(10_000_000, "uusd")]),
+            enter.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AlreadyEntered {});
+
+        // Chips are virtual, only the entry fees are owed
+        let bet = |up: bool, chips: u128| ExecuteMsg::MakeTournamentPrediction {
+            tournament_id: 0,
+            up,
+            chips: Uint128::from(chips),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[]),
+            bet(true, 2_000),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientChips {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[]),
+            bet(true, 600),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player2", &[]),
+            bet(false, 1_000),
+        )
+        .unwrap();
+        let prediction = query_prediction(deps.as_ref(), mock_env(), 0).unwrap();
+        assert!(prediction.up.is_zero());
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.liabilities, Uint128::from(20_000_000u128));
+
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(300);
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player3", &[Coin::new(10_000_000, "uusd")]),
+            enter,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TournamentClosed {});
+        let settle = ExecuteMsg::SettleTournament { tournament_id: 0 };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            settle.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PredictionStillInProgress {});
+
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(455_000_000u128),
+        );
+        env.block.time = env.block.time.plus_seconds(300);
+        execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+
+        // The up bet pays even money once the round expired
+        env.block.time = env.block.time.plus_seconds(30);
+        let entry =
+            query_tournament_entry(deps.as_ref(), env.clone(), 0, "player1".to_string()).unwrap();
+        assert_eq!(entry.chips, Uint128::from(1_600u128));
+        assert!(entry.bets.is_empty());
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            settle.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "player1".to_string(),
+                amount: vec![Coin::new(19_801_980, "uusd")],
+            }))]
+        );
+        let tournament = query_tournament(deps.as_ref(), 0).unwrap();
+        assert!(tournament.settled);
+        assert_eq!(tournament.prize_pool, Uint128::from(20_000_000u128));
+        let state = query_state(deps.as_ref()).unwrap();
+        assert!(state.liabilities.is_zero());
+        let err = execute(deps.as_mut(), env, mock_info("bot", &[]), settle).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyResolved {});
+    }
+
+    #[test]
+    fn proper_jackpot() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let update = ExecuteMsg::UpdateConfig {
+            collector_address: None,
+            collector_ratio: None,
+            fee_model: None,
+            min_fee: None,
+            fee_recipients: None,
+            staking_address: None,
+            discount_tiers: None,
+            referral_share: None,
+            house_seed: None,
+            flat_band: None,
+            bucket_bands: None,
+            fixed_multiplier: None,
+            lock_buffer: None,
+            reveal_window: None,
+            reveal_penalty: None,
+            integrators: None,
+            jackpot_share: Some(Decimal::percent(50)),
+            jackpot_streak: Some(2),
+            jackpot_min_bet: Some(Uint128::from(100_000_000u128)),
+            min_subscription_bet: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[]),
+            update.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), update).unwrap();
+
+        // Player1 wins two rounds in a row against player2, player3 wins them with smaller stakes
+        let bet = |side: Outcome| ExecuteMsg::MakePrediction {
+            side,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
+        let mut env = mock_env();
+        for price in [555_000_000u128, 455_000_000, 355_000_000] {
+            for (player, side, amount) in [
+                ("player1", Outcome::Up, 100_000_000),
+                ("player2", Outcome::Down, 100_000_000),
+                ("player3", Outcome::Up, 10_000_000),
+                ("player4", Outcome::Down, 10_000_000),
+            ] {
+                execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    mock_info(player, &[Coin::new(amount, "uusd")]),
+                    bet(side),
+                )
+                .unwrap();
+            }
+            deps.querier
+                .pool_token(Uint128::new(15_250_000_000u128), Uint128::new(price));
+            env.block.time = env.block.time.plus_seconds(300);
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("bot", &[]),
+                ExecuteMsg::ResolvePrediction { timeframe: None },
+            )
+            .unwrap();
+        }
+        env.block.time = env.block.time.plus_seconds(30);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            ExecuteMsg::ResolveGame {
+                address: "player1".to_string(),
+                round: vec![1, 0],
+            },
+        )
+        .unwrap();
+        // Claimed out of order, the rounds still count in a row
+        let streak = query_streak(deps.as_ref(), "player1".to_string()).unwrap();
+        assert_eq!(streak.streak, 2);
+        assert_eq!(streak.last_round, 1);
+
+        // Half of the winnings fees went to the jackpot
+        let jackpot = query_jackpot(deps.as_ref()).unwrap();
+        let accrued = query_accrued_fees(deps.as_ref()).unwrap();
+        assert_eq!(jackpot.balance, Uint128::from(10_000_000u128));
+        assert_eq!(jackpot.balance, accrued.accrued_fees);
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player2", &[]),
+            ExecuteMsg::ClaimJackpot {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::JackpotNotWon {});
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[]),
+            ExecuteMsg::ClaimJackpot {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "player1".to_string(),
+                amount: vec![Coin::new(9_900_990, "uusd")],
+            }))]
+        );
+        let jackpot = query_jackpot(deps.as_ref()).unwrap();
+        assert!(jackpot.balance.is_zero());
+        assert_eq!(
+            jackpot.winners,
+            vec![JackpotWinResponse {
+                address: "player1".to_string(),
+                amount: Uint128::from(10_000_000u128),
+                streak: 2,
+            }]
+        );
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player1", &[]),
+            ExecuteMsg::ClaimJackpot {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::JackpotNotWon {});
+
+        // Stakes below the minimum do not count toward the streak
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            ExecuteMsg::ResolveGame {
+                address: "player3".to_string(),
+                round: vec![0, 1],
+            },
+        )
+        .unwrap();
+        let game = query_game(deps.as_ref(), "player3".to_string(), 1).unwrap();
+        assert!(game.prize > Uint128::from(10_000_000u128));
+        let streak = query_streak(deps.as_ref(), "player3".to_string()).unwrap();
+        assert_eq!(streak.streak, 0);
+        let err = execute(
+            deps.as_mut(),
+            env,
+            mock_info("player3", &[]),
+            ExecuteMsg::ClaimJackpot {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::JackpotNotWon {});
+    }
+
+    #[test]
+    fn proper_stats() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let bet = |side: Outcome| ExecuteMsg::MakePrediction {
+            side,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
+        for (player, side) in [("player1", Outcome::Up), ("player2", Outcome::Down)] {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(player, &[Coin::new(100_000_000, "uusd")]),
+                bet(side),
+            )
+            .unwrap();
+        }
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(300);
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+
+        // Player3 alone in the second round gets refunded
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("player3", &[Coin::new(50_000_000, "uusd")]),
+            bet(Outcome::Up),
+        )
+        .unwrap();
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(455_000_000u128),
+        );
+        env.block.time = env.block.time.plus_seconds(300);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        env.block.time = env.block.time.plus_seconds(300);
+        execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+
+        env.block.time = env.block.time.plus_seconds(30);
+        for (player, round) in [("player1", 0), ("player2", 0), ("player3", 1)] {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("bot", &[]),
+                ExecuteMsg::ResolveGame {
+                    address: player.to_string(),
+                    round: vec![round],
+                },
+            )
+            .unwrap();
+        }
+        let stats = query_stats(deps.as_ref()).unwrap();
+        assert_eq!(
+            stats,
+            StatsResponse {
+                total_volume: Uint128::from(250_000_000u128),
+                total_fees: Uint128::from(10_000_000u128),
+                total_paid_out: Uint128::from(190_000_000u128),
+                total_refunded: Uint128::from(50_000_000u128),
+                unique_players: 3,
+                rounds_succeeded: 1,
+                rounds_voided: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn proper_current_round() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        for (player, side, amount) in [
+            ("player1", Outcome::Up, 100_000_000),
+            ("player2", Outcome::Down, 50_000_000),
+        ] {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(player, &[Coin::new(amount, "uusd")]),
+                ExecuteMsg::MakePrediction {
+                    side,
+                    referrer: None,
+                    timeframe: None,
+                    amount: None,
+                },
+            )
+            .unwrap();
+        }
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        let current = query_current_round(deps.as_ref(), env.clone(), None).unwrap();
+        assert_eq!(current.open_round.round, 0);
+        assert_eq!(current.open_round.status, PredictionStatus::Open);
+        assert_eq!(current.locked_round, None);
+        assert_eq!(current.seconds_until_lock, 200);
+        assert!(current.betting_open);
+        assert_eq!(current.up, Uint128::from(100_000_000u128));
+        assert_eq!(current.down, Uint128::from(50_000_000u128));
+        assert_eq!(current.total, Uint128::from(150_000_000u128));
+
+        // Betting stops at the closing time until the round is locked
+        env.block.time = env.block.time.plus_seconds(200);
+        let current = query_current_round(deps.as_ref(), env.clone(), None).unwrap();
+        assert_eq!(current.open_round.status, PredictionStatus::Closed);
+        assert_eq!(current.seconds_until_lock, 0);
+        assert!(!current.betting_open);
+
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            ExecuteMsg::ResolvePrediction { timeframe: None },
+        )
+        .unwrap();
+        let current = query_current_round(deps.as_ref(), env, None).unwrap();
+        assert_eq!(current.open_round.round, 1);
+        assert_eq!(current.seconds_until_lock, 300);
+        assert!(current.betting_open);
+        assert!(current.total.is_zero());
+        let locked = current.locked_round.unwrap();
+        assert_eq!(locked.round, 0);
+        assert_eq!(locked.status, PredictionStatus::Locked);
+        assert_eq!(locked.locked_price, Uint128::from(27_477_477u128));
+    }
+
+    #[test]
+    fn proper_withdraw_fees() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Entry,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(100_000_000, "uusd")]),
+            ExecuteMsg::MakePrediction {
+                side: Outcome::Up,
+                referrer: None,
+                timeframe: None,
+                amount: None,
+            },
+        )
+        .unwrap();
+        let accrued = query_accrued_fees(deps.as_ref()).unwrap().accrued_fees;
+        assert_eq!(accrued, Uint128::from(5_000_000u128));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::UpdateConfig {
+                collector_address: None,
+                collector_ratio: None,
+                fee_model: None,
+                min_fee: None,
+                fee_recipients: Some(vec![
+                    FeeRecipientMsg {
+                        address: "collector".to_string(),
+                        weight: 3,
+                    },
+                    FeeRecipientMsg {
+                        address: "treasury".to_string(),
+                        weight: 1,
+                    },
+                ]),
+                staking_address: None,
+                discount_tiers: None,
+                referral_share: None,
+                house_seed: None,
+                flat_band: None,
+                bucket_bands: None,
+                fixed_multiplier: None,
+                lock_buffer: None,
+                reveal_window: None,
+                reveal_penalty: None,
+                integrators: None,
+                jackpot_share: None,
+                jackpot_streak: None,
+                jackpot_min_bet: None,
+                min_subscription_bet: None,
+            },
+        )
+        .unwrap();
+
+        // Only the collector or the admin withdraw, within the accrued fees
+        let withdraw = |amount: u128| ExecuteMsg::WithdrawFees {
+            amount: Uint128::from(amount),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[]),
+            withdraw(1_000),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("collector", &[]),
+            withdraw(0),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::EmptyFunds {});
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("collector", &[]),
+            withdraw(5_000_001),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientFees {});
+
+        // The withdrawn amount is split between the recipients by weight
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("collector", &[]),
+            withdraw(4_000_000),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "collector".to_string(),
+                    amount: vec![Coin::new(2_970_297, "uusd")],
+                })),
+                SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "treasury".to_string(),
+                    amount: vec![Coin::new(990_099, "uusd")],
+                })),
+            ]
+        );
+        assert_eq!(
+            res.attributes,
+            vec![
+                Attribute::new("action", "withdraw_fees"),
+                Attribute::new("collector", "3000000"),
+                Attribute::new("treasury", "1000000"),
+                Attribute::new("amount", "4000000")
+            ]
+        );
+        let accrued = query_accrued_fees(deps.as_ref()).unwrap().accrued_fees;
+        assert_eq!(accrued, Uint128::from(1_000_000u128));
+
+        // Rounding leftover stays accrued
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            withdraw(3),
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                Attribute::new("action", "withdraw_fees"),
+                Attribute::new("collector", "2"),
+                Attribute::new("amount", "2")
+            ]
+        );
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.accrued_fees, Uint128::from(999_998u128));
+        assert_eq!(state.liabilities, Uint128::from(95_999_998u128));
+    }
+
+    #[test]
+    fn proper_auto_compound_balance() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(50_000_000, "uusd")]),
+            ExecuteMsg::Deposit {},
+        )
+        .unwrap();
+
+        // Opting in does not bet the deposit
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[]),
+            ExecuteMsg::SetAutoCompound {
+                enabled: true,
+                side: Some(Outcome::Up),
+                timeframe: None,
+            },
+        )
+        .unwrap();
+        let balance = query_balance(deps.as_ref(), "player1".to_string()).unwrap();
+        assert_eq!(balance.balance, Uint128::from(50_000_000u128));
+        let current = query_current_round(deps.as_ref(), mock_env(), None).unwrap();
+        assert!(current.total.is_zero());
+
+        for (player, side) in [("player1", Outcome::Up), ("player2", Outcome::Down)] {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(player, &[Coin::new(100_000_000, "uusd")]),
+                ExecuteMsg::MakePrediction {
+                    side,
+                    referrer: None,
+                    timeframe: None,
+                    amount: None,
+                },
+            )
+            .unwrap();
+        }
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(300);
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(455_000_000u128),
+        );
+        env.block.time = env.block.time.plus_seconds(300);
+        execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+
+        // Only the claim is bet again, the deposit stays in the balance
+        env.block.time = env.block.time.plus_seconds(30);
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            ExecuteMsg::ResolveGame {
+                address: "player1".to_string(),
+                round: vec![0],
+            },
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        assert!(res
+            .attributes
+            .contains(&Attribute::new("compounded", "190000000")));
+        let game = query_game(deps.as_ref(), "player1".to_string(), 2).unwrap();
+        assert_eq!(game.up, Uint128::from(190_000_000u128));
+        let balance = query_balance(deps.as_ref(), "player1".to_string()).unwrap();
+        assert_eq!(balance.balance, Uint128::from(50_000_000u128));
+    }
+
+    #[test]
+    fn proper_tournament_flat() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::UpdateConfig {
+                collector_address: None,
+                collector_ratio: None,
+                fee_model: None,
+                min_fee: None,
+                fee_recipients: None,
+                staking_address: None,
+                discount_tiers: None,
+                referral_share: None,
+                house_seed: None,
+                flat_band: Some(Decimal::percent(1)),
+                bucket_bands: None,
+                fixed_multiplier: None,
+                lock_buffer: None,
+                reveal_window: None,
+                reveal_penalty: None,
+                integrators: None,
+                jackpot_share: None,
+                jackpot_streak: None,
+                jackpot_min_bet: None,
+                min_subscription_bet: None,
+            },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::CreateTournament {
+                timeframe: None,
+                start_round: 1,
+                end_round: 1,
+                entry_fee: Uint128::from(10_000_000u128),
+                starting_chips: Uint128::from(1_000u128),
+                winners: 1,
+            },
+        )
+        .unwrap();
+        for player in ["player1", "player2"] {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(player, &[Coin::new(10_000_000, "uusd")]),
+                ExecuteMsg::EnterTournament { tournament_id: 0 },
+            )
+            .unwrap();
+        }
+
+        // Round 1 opens with the flat band
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(300);
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        for (player, up, chips) in [("player1", true, 600u128), ("player2", false, 1_000)] {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(player, &[]),
+                ExecuteMsg::MakeTournamentPrediction {
+                    tournament_id: 0,
+                    up,
+                    chips: Uint128::from(chips),
+                },
+            )
+            .unwrap();
+        }
+        for _ in 0..2 {
+            env.block.time = env.block.time.plus_seconds(300);
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("bot", &[]),
+                msg.clone(),
+            )
+            .unwrap();
+        }
+
+        // The price stayed within the band, no side wins and the chips are returned
+        env.block.time = env.block.time.plus_seconds(30);
+        let prediction = query_prediction(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(prediction.fixed_outcome, Some(Outcome::Flat));
+        for player in ["player1", "player2"] {
+            let entry =
+                query_tournament_entry(deps.as_ref(), env.clone(), 0, player.to_string()).unwrap();
+            assert_eq!(entry.chips, Uint128::from(1_000u128));
+            assert!(entry.bets.is_empty());
+        }
+    }
+
+    #[test]
+    fn proper_subscription_rollback() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[Coin::new(20_000_000, "uusd")]),
+            ExecuteMsg::Subscribe {
+                side: Outcome::Up,
+                amount_per_round: Uint128::from(10_000_000u128),
+                rounds: 2,
+                timeframe: None,
+            },
+        )
+        .unwrap();
+
+        // The discount query fails once the bet is otherwise accepted
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::UpdateConfig {
+                collector_address: None,
+                collector_ratio: None,
+                fee_model: None,
+                min_fee: None,
+                fee_recipients: None,
+                staking_address: Some("unreachable".to_string()),
+                discount_tiers: Some(vec![DiscountTier {
+                    min_stake: Uint128::from(1_000u128),
+                    discount: Decimal::percent(10),
+                }]),
+                referral_share: None,
+                house_seed: None,
+                flat_band: None,
+                bucket_bands: None,
+                fixed_multiplier: None,
+                lock_buffer: None,
+                reveal_window: None,
+                reveal_penalty: None,
+                integrators: None,
+                jackpot_share: None,
+                jackpot_streak: None,
+                jackpot_min_bet: None,
+                min_subscription_bet: None,
+            },
+        )
+        .unwrap();
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(300);
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            ExecuteMsg::ResolvePrediction { timeframe: None },
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .contains(&Attribute::new("subscriptions_refunded", "20000000")));
+
+        // Nothing of the refused bet is left behind
+        let state = query_state(deps.as_ref()).unwrap();
+        assert!(state.liabilities.is_zero());
+        let stats = query_stats(deps.as_ref()).unwrap();
+        assert!(stats.total_volume.is_zero());
+        assert_eq!(stats.unique_players, 0);
+        let prediction = query_prediction(deps.as_ref(), env, 1).unwrap();
+        assert!(prediction.up.is_zero());
+        assert_eq!(prediction.pending_games, 0);
+    }
+
+    #[test]
+    fn proper_auto_compound_refused() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player1", &[]),
+            ExecuteMsg::SetAutoCompound {
+                enabled: true,
+                side: None,
+                timeframe: None,
+            },
+        )
+        .unwrap();
+        for (player, side) in [("player1", Outcome::Up), ("player2", Outcome::Down)] {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(player, &[Coin::new(100_000_000, "uusd")]),
+                ExecuteMsg::MakePrediction {
+                    side,
+                    referrer: None,
+                    timeframe: None,
+                    amount: None,
+                },
+            )
+            .unwrap();
+        }
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(555_000_000u128),
+        );
+        let mut env = mock_env();
+        let msg = ExecuteMsg::ResolvePrediction { timeframe: None };
+        env.block.time = env.block.time.plus_seconds(300);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        deps.querier.pool_token(
+            Uint128::new(15_250_000_000u128),
+            Uint128::new(455_000_000u128),
+        );
+        env.block.time = env.block.time.plus_seconds(300);
+        execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), msg).unwrap();
+
+        // The discount query of the compounded bet fails
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            ExecuteMsg::UpdateConfig {
+                collector_address: None,
+                collector_ratio: None,
+                fee_model: None,
+                min_fee: None,
+                fee_recipients: None,
+                staking_address: Some("unreachable".to_string()),
+                discount_tiers: Some(vec![DiscountTier {
+                    min_stake: Uint128::from(1_000u128),
+                    discount: Decimal::percent(10),
+                }]),
+                referral_share: None,
+                house_seed: None,
+                flat_band: None,
+                bucket_bands: None,
+                fixed_multiplier: None,
+                lock_buffer: None,
+                reveal_window: None,
+                reveal_penalty: None,
+                integrators: None,
+                jackpot_share: None,
+                jackpot_streak: None,
+                jackpot_min_bet: None,
+                min_subscription_bet: None,
+            },
+        )
+        .unwrap();
+        env.block.time = env.block.time.plus_seconds(30);
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            ExecuteMsg::ResolveGame {
+                address: "player1".to_string(),
+                round: vec![0],
+            },
+        )
+        .unwrap();
+
+        // The refused claim is credited and nothing of the bet is counted
+        assert!(res.messages.is_empty());
+        assert!(res
+            .attributes
+            .contains(&Attribute::new("credited", "190000000")));
+        assert!(!res
+            .attributes
+            .iter()
+            .any(|attribute| attribute.key == "compounded"));
+        let balance = query_balance(deps.as_ref(), "player1".to_string()).unwrap();
+        assert_eq!(balance.balance, Uint128::from(190_000_000u128));
+        assert!(query_game(deps.as_ref(), "player1".to_string(), 2).is_err());
+        let stats = query_stats(deps.as_ref()).unwrap();
+        assert_eq!(stats.total_volume, Uint128::from(200_000_000u128));
+        let state = query_state(deps.as_ref()).unwrap();
+        assert_eq!(state.liabilities, Uint128::from(200_000_000u128));
+    }
+
+    #[test]
+    fn proper_streak_void_round() {
+        let mut deps = mock_dependencies_custom(&[Coin::new(10_000_000_000, "uusd")]);
+        let msg = InstantiateMsg {
+            pool_address: "terraswap".to_string(),
+            collector_address: "collector".to_string(),
+            round_time: 300,
+            limit_time: 30,
+            denom: "uusd".to_string(),
+            collector_ratio: Decimal::percent(5),
+            fee_model: FeeModel::Winnings,
+            min_fee: Uint128::zero(),
+            epoch_rounds: 100,
+            leaderboard_min_rounds: 1,
+            min_subscription_bet: Uint128::from(1_000_000u128),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // Player1 wins rounds 0 and 2, alone in round 1 it is refunded
+        let bet = |side: Outcome| ExecuteMsg::MakePrediction {
+            side,
+            referrer: None,
+            timeframe: None,
+            amount: None,
+        };
+        let mut env = mock_env();
+        for (round, price) in [555_000_000u128, 455_000_000, 355_000_000, 255_000_000]
+            .iter()
+            .enumerate()
+        {
+            let mut bets = vec![("player1", Outcome::Up)];
+            if round != 1 {
+                bets.push(("player2", Outcome::Down));
+            }
+            for (player, side) in bets {
+                execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    mock_info(player, &[Coin::new(100_000_000, "uusd")]),
+                    bet(side),
+                )
+                .unwrap();
+            }
+            deps.querier
+                .pool_token(Uint128::new(15_250_000_000u128), Uint128::new(*price));
+            env.block.time = env.block.time.plus_seconds(300);
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("bot", &[]),
+                ExecuteMsg::ResolvePrediction { timeframe: None },
+            )
+            .unwrap();
+        }
+        env.block.time = env.block.time.plus_seconds(30);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bot", &[]),
+            ExecuteMsg::ResolveGame {
+                address: "player1".to_string(),
+                round: vec![0, 1],
+            },
+        )
+        .unwrap();
+        let game = query_game(deps.as_ref(), "player1".to_string(), 1).unwrap();
+        assert_eq!(game.prize, Uint128::from(100_000_000u128));
+
+        // The refunded round leaves the streak as it is and does not break it
+        let streak = query_streak(deps.as_ref(), "player1".to_string()).unwrap();
+        assert_eq!(streak.streak, 1);
+        assert_eq!(streak.last_round, 0);
+        execute(
+            deps.as_mut(),
+            env,
+            mock_info("bot", &[]),
+            ExecuteMsg::ResolveGame {
+                address: "player1".to_string(),
+                round: vec![2],
+            },
+        )
+        .unwrap();
+        let streak = query_streak(deps.as_ref(), "player1".to_string()).unwrap();
+        assert_eq!(streak.streak, 2);
+        assert_eq!(streak.last_round, 2);
     }
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{OverflowError, StdError};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -6,6 +6,9 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
@@ -22,6 +25,126 @@ pub enum ContractError {
     EmptyFunds {},
     #[error("Already resolved")]
     AlreadyResolved {},
+
+    #[error("Payouts exceed the pool of round {round}")]
+    PoolExceeded { round: u64 },
+
+    #[error("Contract balance does not cover its liabilities")]
+    Insolvent {},
+
+    #[error("Fee ratio must not exceed one")]
+    InvalidFee {},
+
+    #[error("Fee recipients need a positive total weight")]
+    InvalidFeeRecipients {},
+
+    #[error("Amount exceeds the accrued fees")]
+    InsufficientFees {},
+
+    #[error("Referrer must be another address")]
+    InvalidReferrer {},
+
+    #[error("Amount exceeds the house balance")]
+    InsufficientHouseBalance {},
+
+    #[error("Flat band must be below one")]
+    InvalidFlatBand {},
+
+    #[error("Flat outcome is not enabled for this round")]
+    FlatDisabled {},
+
+    #[error("Bucket bands must be positive and strictly increasing")]
+    InvalidBuckets {},
+
+    #[error("Bucket does not exist for this round")]
+    InvalidBucket {},
+
+    #[error("Fixed odds multiplier must be above one")]
+    InvalidMultiplier {},
+
+    #[error("Fixed odds are not enabled for this round")]
+    FixedOddsDisabled {},
+
+    #[error("House liquidity does not cover the exposure")]
+    ExposureExceeded {},
+
+    #[error("Timeframe does not exist or has invalid durations")]
+    InvalidTimeframe {},
+
+    #[error("Lock buffer and reveal window must fit in the round time of every timeframe")]
+    InvalidLockBuffer {},
+
+    #[error("Betting is closed for this round")]
+    BettingClosed {},
+
+    #[error("Commit reveal is not enabled for this round")]
+    CommitRevealDisabled {},
+
+    #[error("A commitment is already pending for this round")]
+    AlreadyCommitted {},
+
+    #[error("Reveal window is not open")]
+    RevealClosed {},
+
+    #[error("Reveal does not match any commitment")]
+    InvalidReveal {},
+
+    #[error("Subscription needs a positive amount and rounds, funded exactly")]
+    InvalidSubscription {},
+
+    #[error("A subscription already exists on this timeframe")]
+    AlreadySubscribed {},
+
+    #[error("Subscription not found")]
+    SubscriptionNotFound {},
+
+    #[error("Too many subscriptions on this timeframe")]
+    TooManySubscriptions {},
+
+    #[error("Internal balance is too low")]
+    InsufficientBalance {},
+
+    #[error("Tournament needs a positive entry fee, chips and winners over a short round range")]
+    InvalidTournament {},
+
+    #[error("Tournament not found")]
+    TournamentNotFound {},
+
+    #[error("Tournament is not accepting this action")]
+    TournamentClosed {},
+
+    #[error("Entry must be funded with exactly the entry fee")]
+    InvalidEntryFee {},
+
+    #[error("Already entered this tournament")]
+    AlreadyEntered {},
+
+    #[error("Too many entrants in this tournament")]
+    TooManyEntrants {},
+
+    #[error("Not entered in this tournament")]
+    NotEntered {},
+
+    #[error("Not enough chips")]
+    InsufficientChips {},
+
+    #[error("Winning streak is too short to claim the jackpot")]
+    JackpotNotWon {},
+
+    #[error("Jackpot is empty")]
+    EmptyJackpot {},
+
+    #[error("Referral rewards require the winnings fee model")]
+    ReferralWithoutWinningsFee {},
+
+    #[error("Bet does not cover the entry fee")]
+    BetBelowFee {},
+
+    #[error("Epoch rounds must be greater than zero")]
+    InvalidEpochRounds {},
+
+    #[error("Minimum subscription bet must be greater than zero")]
+    InvalidMinSubscriptionBet {},
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
@@ -0,0 +1,59 @@
+use std::cmp::Ordering;
+
+use cosmwasm_std::{CanonicalAddr, Uint256};
+
+use crate::msg::LeaderboardKind;
+use crate::state::{LeaderboardEntry, UserStats};
+
+pub const LEADERBOARD_SIZE: usize = 20;
+
+pub fn kind_key(kind: LeaderboardKind) -> &'static [u8] {
+    match kind {
+        LeaderboardKind::NetWinnings => b"net_winnings",
+        LeaderboardKind::WinRate => b"win_rate",
+        LeaderboardKind::Volume => b"volume",
+    }
+}
+
+fn is_eligible(kind: LeaderboardKind, stats: &UserStats, min_rounds: u64) -> bool {
+    match kind {
+        LeaderboardKind::NetWinnings => stats.payout > stats.volume,
+        LeaderboardKind::WinRate => stats.rounds_played != 0 && stats.rounds_played >= min_rounds,
+        LeaderboardKind::Volume => !stats.volume.is_zero(),
+    }
+}
+
+/// Compare two stats by score, values are cross multiplied to avoid rounding and negative nets
+fn compare(kind: LeaderboardKind, a: &UserStats, b: &UserStats) -> Ordering {
+    match kind {
+        LeaderboardKind::NetWinnings => (Uint256::from(a.payout) + Uint256::from(b.volume))
+            .cmp(&(Uint256::from(b.payout) + Uint256::from(a.volume))),
+        LeaderboardKind::WinRate => {
+            let a_rate = u128::from(a.rounds_won) * u128::from(b.rounds_played);
+            let b_rate = u128::from(b.rounds_won) * u128::from(a.rounds_played);
+            a_rate
+                .cmp(&b_rate)
+                .then(a.rounds_played.cmp(&b.rounds_played))
+        }
+        LeaderboardKind::Volume => a.volume.cmp(&b.volume),
+    }
+}
+
+/// Insert or replace the stats of an address and keep the top entries sorted descending
+pub fn update_board(
+    board: &mut Vec<LeaderboardEntry>,
+    kind: LeaderboardKind,
+    address: &CanonicalAddr,
+    stats: &UserStats,
+    min_rounds: u64,
+) {
+    board.retain(|entry| &entry.address != address);
+    if is_eligible(kind, stats, min_rounds) {
+        board.push(LeaderboardEntry {
+            address: address.clone(),
+            stats: stats.clone(),
+        });
+        board.sort_by(|a, b| compare(kind, &b.stats, &a.stats));
+        board.truncate(LEADERBOARD_SIZE);
+    }
+}
@@ -1,8 +1,10 @@
 pub mod contract;
 mod error;
+mod leaderboard;
 pub mod msg;
 pub mod state;
 mod taxation;
+mod tournament;
 
 #[cfg(test)]
 mod mock_querier;
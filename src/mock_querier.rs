@@ -1,25 +1,22 @@
+use std::collections::HashMap;
+
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_slice, to_binary, Addr, Api, BalanceResponse, BankQuery, Binary, Coin, ContractResult,
-    Decimal, Empty, OwnedDeps, Querier, QuerierResult, QueryRequest, Response, StdError, StdResult,
-    SystemError, SystemResult, Uint128, WasmQuery,
+    from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
 };
-use std::str::FromStr;
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper};
-use terraswap::asset::Asset;
-use terraswap::asset::AssetInfo::{NativeToken, Token};
-use terraswap::pair::PoolResponse;
 
-pub const MOCK_HUB_CONTRACT_ADDR: &str = "hub";
-pub const MOCK_CW20_CONTRACT_ADDR: &str = "lottery";
-//pub const MOCK_REWARD_CONTRACT_ADDR: &str = "reward";
-pub const MOCK_TOKEN_CONTRACT_ADDR: &str = "token";
+use crate::msg::{
+    OracleListPriceFeedResponse, OraclePriceFeedQueryMsg, OraclePriceFeedResponse,
+    OraclePriceFeedStateResponse, StakingHolderResponse, StakingQueryMsg,
+};
 
 pub fn mock_dependencies_custom(
     contract_balance: &[Coin],
 ) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
     let custom_querier =
-        WasmMockQuerier::new(MockQuerier::new(&[(&MOCK_CONTRACT_ADDR, contract_balance)]));
+        WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]));
     OwnedDeps {
         storage: MockStorage::default(),
         api: MockApi::default(),
@@ -30,6 +27,7 @@ pub fn mock_dependencies_custom(
 pub struct WasmMockQuerier {
     base: MockQuerier<TerraQueryWrapper>,
     pool_response: PoolInfoResponse,
+    staked: HashMap<String, Uint128>,
 }
 
 #[derive(Clone, Default)]
@@ -69,28 +67,53 @@ impl WasmMockQuerier {
                 println!("{}", contract_addr);
                 if contract_addr == "terraswap" {
                     println!("{:?}", request);
-                    let msg_pool = PoolResponse {
-                        assets: [
-                            Asset {
-                                info: NativeToken {
-                                    denom: "uusd".to_string(),
-                                },
-                                amount: self.pool_response.amount_native,
-                            },
-                            Asset {
-                                info: NativeToken {
-                                    denom: "uluna".to_string(),
-                                },
-                                amount: self.pool_response.amount_token,
-                            },
-                        ],
-                        total_share: Uint128::from(12949588085_u128),
+                    let price = Uint128::from(1_000_000_u128).multiply_ratio(
+                        self.pool_response.amount_native,
+                        self.pool_response.amount_token,
+                    );
+                    return match from_binary(msg).unwrap() {
+                        OraclePriceFeedQueryMsg::State {} => {
+                            let msg_state = OraclePriceFeedStateResponse {
+                                pool_address: "terraswap".to_string(),
+                                round: 0,
+                                denom_one: "uluna".to_string(),
+                                denom_two: "uusd".to_string(),
+                            };
+                            SystemResult::Ok(ContractResult::from(to_binary(&msg_state)))
+                        }
+                        OraclePriceFeedQueryMsg::GetListPriceFeed { .. } => {
+                            let msg_list = OracleListPriceFeedResponse {
+                                list: (0..10)
+                                    .map(|_| OraclePriceFeedResponse {
+                                        timestamp: u64::MAX,
+                                        price,
+                                        worker: "worker".to_string(),
+                                    })
+                                    .collect(),
+                            };
+                            SystemResult::Ok(ContractResult::from(to_binary(&msg_list)))
+                        }
+                    };
+                }
+                if contract_addr == "staking" {
+                    let StakingQueryMsg::Holder { address } = from_binary(msg).unwrap();
+                    let msg_holder = StakingHolderResponse {
+                        balance: self.staked.get(&address).copied().unwrap_or_default(),
+                        address,
                     };
-                    return SystemResult::Ok(ContractResult::from(to_binary(&msg_pool)));
+                    return SystemResult::Ok(ContractResult::from(to_binary(&msg_holder)));
+                }
+                if contract_addr == "unreachable" {
+                    return SystemResult::Err(SystemError::NoSuchContract {
+                        addr: contract_addr.clone(),
+                    });
                 }
                 panic!("DO NOT ENTER HERE")
             }
-            QueryRequest::Custom(TerraQueryWrapper { route, query_data }) => match query_data {
+            QueryRequest::Custom(TerraQueryWrapper {
+                route: _,
+                query_data,
+            }) => match query_data {
                 TerraQuery::TaxRate {} => {
                     let res = TaxRateResponse {
                         rate: Decimal::percent(1),
@@ -114,10 +137,20 @@ impl WasmMockQuerier {
         WasmMockQuerier {
             base,
             pool_response: PoolInfoResponse::default(),
+            staked: HashMap::new(),
         }
     }
     // configure the mint whitelist mock querier
     pub fn pool_token(&mut self, amount_native: Uint128, amount_token: Uint128) {
         self.pool_response = PoolInfoResponse::new(amount_native, amount_token)
     }
+    // configure the contract balance, funds attached to a message are already included
+    pub fn contract_balance(&mut self, balance: &[Coin]) {
+        self.base
+            .update_balance(MOCK_CONTRACT_ADDR, balance.to_vec());
+    }
+    // configure the LOTA staked by an address
+    pub fn stake(&mut self, address: &str, amount: Uint128) {
+        self.staked.insert(address.to_string(), amount);
+    }
 }
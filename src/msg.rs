@@ -1,4 +1,4 @@
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_std::{Binary, Decimal, Order, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -10,17 +10,194 @@ pub struct InstantiateMsg {
     pub limit_time: u64,
     pub denom: String,
     pub collector_ratio: Decimal,
+    pub fee_model: FeeModel,
+    pub min_fee: Uint128,
+    /// Default timeframe rounds lasting each leaderboard epoch, epochs are consecutive fixed windows
+    pub epoch_rounds: u64,
+    pub leaderboard_min_rounds: u64,
+    /// Smallest subscription bet per round, in the configured denom
+    pub min_subscription_bet: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeRecipientMsg {
+    pub address: String,
+    pub weight: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeModel {
+    /// Fee on the gross prize of winners when claiming
+    Winnings,
+    /// Fee on the whole pool when the round is resolved
+    TotalPool,
+    /// Fee on the losing side when the round is resolved
+    LosingPool,
+    /// Fee on each bet when entering
+    Entry,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Up,
+    Down,
+    Flat,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeSettings {
+    pub model: FeeModel,
+    pub rate: Decimal,
+    pub min_fee: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DiscountTier {
+    /// Minimum LOTA staked to reach the tier
+    pub min_stake: Uint128,
+    /// Share of the winnings fee waived
+    pub discount: Decimal,
+}
+
+/// Virtual bet on a round, paid at even money on the price outcome and returned on a flat one
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TournamentBet {
+    pub round: u64,
+    pub up: bool,
+    pub chips: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     /// Make a prediction on the current round
-    MakePrediction { up: bool },
+    MakePrediction {
+        /// Flat bets on the price staying within the flat band
+        side: Outcome,
+        /// Recorded on the first referral only
+        referrer: Option<String>,
+        /// Round series to bet on, the default timeframe when missing
+        timeframe: Option<u32>,
+        /// Stake taken from the internal balance when no funds are attached
+        amount: Option<Uint128>,
+    },
+    /// Bet on the bucket of the resolved price relative to the locked price
+    MakeBucketPrediction {
+        bucket: u32,
+        referrer: Option<String>,
+        timeframe: Option<u32>,
+    },
+    /// Bet at the multiplier quoted by the house, paid whatever the pools
+    MakeFixedOddsPrediction {
+        up: bool,
+        referrer: Option<String>,
+        timeframe: Option<u32>,
+    },
+    /// Hide the side of a bet behind the sha256 of `<sender>:up:<salt>` or `<sender>:down:<salt>`
+    CommitPrediction {
+        hash: Binary,
+        referrer: Option<String>,
+        timeframe: Option<u32>,
+    },
+    /// Reveal the committed side during the reveal window, the stake then enters the pool
+    RevealPrediction {
+        up: bool,
+        salt: String,
+        timeframe: Option<u32>,
+    },
+    /// Escrow `amount_per_round * rounds` to bet on a side in each new round of the timeframe
+    /// from the configured minimum per round, a refused bet cancels it and refunds the escrow left
+    Subscribe {
+        side: Outcome,
+        amount_per_round: Uint128,
+        rounds: u64,
+        timeframe: Option<u32>,
+    },
+    /// Stop the subscription of the sender and refund its unspent escrow
+    CancelSubscription { timeframe: Option<u32> },
+    /// Opt in to bet claims again instead of receiving them, disabling keeps the balance
+    SetAutoCompound {
+        enabled: bool,
+        side: Option<Outcome>,
+        timeframe: Option<u32>,
+    },
+    /// Bet the attached funds for the beneficiary, only allowed to whitelisted integrators
+    MakePredictionFor {
+        beneficiary: String,
+        /// Same sides as `MakePrediction`
+        side: Outcome,
+        /// Round series to bet on, the default timeframe when missing
+        timeframe: Option<u32>,
+    },
+    /// Credit the attached funds to the internal balance, claims are then credited too
+    Deposit {},
+    /// Send funds from the internal balance, emptying it sends claims again
+    Withdraw { amount: Uint128 },
     /// Resolve will collect prize or refund if prediction fail
     ResolveGame { address: String, round: Vec<u64> },
-    /// Finish round will start a new round
-    ResolvePrediction {},
+    /// Finish round will start a new round of the timeframe
+    ResolvePrediction { timeframe: Option<u32> },
+    /// Send the accumulated rounding dust to the collector, admin only
+    SweepDust {},
+    /// Update the config, admin only
+    UpdateConfig {
+        collector_address: Option<String>,
+        collector_ratio: Option<Decimal>,
+        fee_model: Option<FeeModel>,
+        min_fee: Option<Uint128>,
+        fee_recipients: Option<Vec<FeeRecipientMsg>>,
+        staking_address: Option<String>,
+        discount_tiers: Option<Vec<DiscountTier>>,
+        referral_share: Option<Decimal>,
+        house_seed: Option<Uint128>,
+        flat_band: Option<Decimal>,
+        bucket_bands: Option<Vec<Decimal>>,
+        fixed_multiplier: Option<Decimal>,
+        lock_buffer: Option<u64>,
+        reveal_window: Option<u64>,
+        reveal_penalty: Option<Decimal>,
+        integrators: Option<Vec<String>>,
+        jackpot_share: Option<Decimal>,
+        jackpot_streak: Option<u32>,
+        jackpot_min_bet: Option<Uint128>,
+        min_subscription_bet: Option<Uint128>,
+    },
+    /// Send the accrued fees to the fee recipients by weight
+    DistributeFees {},
+    /// Withdraw part of the accrued fees to the fee recipients by weight, collector or admin only
+    WithdrawFees { amount: Uint128 },
+    /// Send the pending referral rewards of the sender
+    ClaimReferralRewards {},
+    /// Send the whole jackpot to the sender once its winning streak is long enough
+    ClaimJackpot {},
+    /// Add liquidity to the house pool, admin only
+    DepositHouse {},
+    /// Withdraw free liquidity from the house pool, admin only
+    WithdrawHouse { amount: Uint128 },
+    /// Start a new series of rounds running alongside the others, admin only
+    AddTimeframe { round_time: u64, limit_time: u64 },
+    /// Open a tournament over the rounds of a timeframe, admin only
+    CreateTournament {
+        timeframe: Option<u32>,
+        start_round: u64,
+        end_round: u64,
+        entry_fee: Uint128,
+        starting_chips: Uint128,
+        winners: u32,
+    },
+    /// Pay the entry fee before the first round is locked to receive the starting chips
+    EnterTournament { tournament_id: u64 },
+    /// Bet chips on the open round of the tournament timeframe
+    MakeTournamentPrediction {
+        tournament_id: u64,
+        up: bool,
+        chips: Uint128,
+    },
+    /// Pay the prize pool to the top entrants once every round expired
+    SettleTournament { tournament_id: u64 },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -37,17 +214,97 @@ pub enum QueryMsg {
     /// Retrieve all predictions for info
     Predictions {
         start_after: Option<u64>,
+        start_before: Option<u64>,
+        limit: Option<u32>,
+        order: Option<OrderBy>,
+        status: Option<PredictionStatus>,
+        /// Only rounds claimable with games not resolved yet
+        pending_claims: Option<bool>,
+        /// Only rounds of this timeframe
+        timeframe: Option<u32>,
+    },
+    /// Retrieve the open round, the locked round and whether betting is open
+    CurrentRound { timeframe: Option<u32> },
+    /// Retrieve the round series and their durations
+    Timeframes {},
+    /// Retrieve the internal balance and auto compound settings of an address
+    Balance { address: String },
+    /// Retrieve the subscription of an address on a timeframe
+    Subscription {
+        address: String,
+        timeframe: Option<u32>,
+    },
+    /// Retrieve a tournament and its prize pool
+    Tournament { tournament_id: u64 },
+    /// Retrieve the chips and pending bets of a tournament entrant
+    TournamentEntry { tournament_id: u64, address: String },
+    /// Retrieve the protocol-wide counters
+    Stats {},
+    /// Compare the contract balance with its liabilities
+    Solvency {},
+    /// Retrieve the fees accrued and not withdrawn yet
+    AccruedFees {},
+    /// Retrieve the aggregated stats of an address
+    UserStats { address: String },
+    /// Retrieve the referred users and rewards of a referrer
+    ReferralStats { address: String },
+    /// Retrieve the house liquidity and its profit and loss
+    House {},
+    /// Retrieve the jackpot, its rules and the latest winners
+    Jackpot {},
+    /// Retrieve the current winning streak of an address
+    Streak { address: String },
+    /// Retrieve the top players by kind for all-time or an epoch
+    Leaderboard {
+        kind: LeaderboardKind,
+        period: LeaderboardPeriod,
         limit: Option<u32>,
     },
     // /// Retrieve all games
     // Games { start_after: Option<u64>, limit: Option<u64> },
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderBy {
+    Ascending,
+    Descending,
+}
+
+impl From<OrderBy> for Order {
+    fn from(order: OrderBy) -> Order {
+        match order {
+            OrderBy::Ascending => Order::Ascending,
+            OrderBy::Descending => Order::Descending,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardKind {
+    /// Payout minus volume, only players in profit are ranked
+    NetWinnings,
+    /// Rounds won over rounds played, above the minimum rounds threshold
+    WinRate,
+    /// Total amount wagered
+    Volume,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardPeriod {
+    AllTime {},
+    /// Fixed window of time, not a rolling one, the current epoch if none
+    Epoch {
+        epoch: Option<u64>,
+    },
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum OraclePriceFeedQueryMsg {
-    State{},
+    State {},
     GetListPriceFeed {
         start_after: Option<u64>,
         limit: Option<u32>,
@@ -58,17 +315,67 @@ pub enum OraclePriceFeedQueryMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct StateResponse {
     pub round: u64,
+    pub dust: Uint128,
+    pub liabilities: Uint128,
+    pub accrued_fees: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatsResponse {
+    pub total_volume: Uint128,
+    pub total_fees: Uint128,
+    pub total_paid_out: Uint128,
+    pub total_refunded: Uint128,
+    pub unique_players: u64,
+    pub rounds_succeeded: u64,
+    pub rounds_voided: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
+    pub admin: String,
     pub pool_address: String,
+    pub collector_address: String,
     pub round_time: u64,
     pub limit_time: u64,
     pub denom: String,
+    pub collector_fee: Decimal,
+    pub fee_model: FeeModel,
+    pub min_fee: Uint128,
+    pub fee_recipients: Vec<FeeRecipientMsg>,
+    pub staking_address: Option<String>,
+    pub discount_tiers: Vec<DiscountTier>,
+    pub referral_share: Decimal,
+    pub house_seed: Uint128,
+    pub flat_band: Decimal,
+    pub bucket_bands: Vec<Decimal>,
+    pub fixed_multiplier: Decimal,
+    pub lock_buffer: u64,
+    pub reveal_window: u64,
+    pub reveal_penalty: Decimal,
+    pub integrators: Vec<String>,
+    pub jackpot_share: Decimal,
+    pub jackpot_streak: u32,
+    pub jackpot_min_bet: Uint128,
+    pub epoch_rounds: u64,
+    pub epoch_start: u64,
+    pub leaderboard_min_rounds: u64,
+    pub min_subscription_bet: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StakingQueryMsg {
+    Holder { address: String },
 }
 
 // We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakingHolderResponse {
+    pub address: String,
+    pub balance: Uint128,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct OraclePriceFeedStateResponse {
     pub pool_address: String,
@@ -86,18 +393,225 @@ pub struct OraclePriceFeedResponse {
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct OracleListPriceFeedResponse {
-    pub list: Vec<OraclePriceFeedResponse>
+    pub list: Vec<OraclePriceFeedResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PredictionStatus {
+    /// Current round accepting bets
+    Open,
+    /// Current round past its closing time, awaiting the price lock
+    Closed,
+    /// Price locked and awaiting resolution
+    Locked,
+    /// Resolved with a winning side
+    Resolved,
+    /// Resolved without winner, bets are refunded
+    Voided,
+    /// Not resolved before the expire time, will be voided
+    Expired,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct PredictionInfo {
+    pub round: u64,
+    pub status: PredictionStatus,
+    pub timeframe: u32,
     pub up: Uint128,
     pub down: Uint128,
+    pub flat: Uint128,
+    pub flat_band: Decimal,
+    pub bucket_bands: Vec<Decimal>,
+    pub buckets: Vec<Uint128>,
+    pub bucket: Option<u32>,
+    pub bucket_fee: Uint128,
+    pub fixed_multiplier: Decimal,
+    pub fixed_up: Uint128,
+    pub fixed_down: Uint128,
+    pub fixed_reserved_up: Uint128,
+    pub fixed_reserved_down: Uint128,
+    pub fixed_outcome: Option<Outcome>,
+    pub reveal_window: u64,
+    pub reveal_penalty: Decimal,
+    pub committed: Uint128,
     pub locked_price: Uint128,
     pub resolved_price: Uint128,
     pub closing_time: u64,
     pub expire_time: u64,
     pub success: bool,
     pub is_up: Option<bool>,
-    pub oracle_price_worker: Option<String>
+    pub is_flat: bool,
+    pub oracle_price_worker: Option<String>,
+    pub pending_games: u64,
+    pub claimed: Uint128,
+    pub fee_settings: Option<FeeSettings>,
+    pub fee: Uint128,
+    pub house_up: Uint128,
+    pub house_down: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UserStatsResponse {
+    pub address: String,
+    pub rounds_played: u64,
+    pub rounds_won: u64,
+    pub rounds_refunded: u64,
+    pub volume: Uint128,
+    pub payout: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReferralStatsResponse {
+    pub address: String,
+    pub referred_users: u64,
+    pub total_rewards: Uint128,
+    pub pending_rewards: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LeaderboardResponse {
+    pub kind: LeaderboardKind,
+    pub epoch: Option<u64>,
+    pub entries: Vec<UserStatsResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PredictionsResponse {
+    pub predictions: Vec<PredictionInfo>,
+    /// Last round scanned when more rounds remain, to pass as start_after or start_before
+    pub next_start: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GameResponse {
+    pub address: String,
+    pub round: u64,
+    pub up: Uint128,
+    pub down: Uint128,
+    pub flat: Uint128,
+    pub buckets: Vec<Uint128>,
+    pub fixed_up: Uint128,
+    pub fixed_down: Uint128,
+    pub multiplier: Decimal,
+    pub commitment: Option<Binary>,
+    pub committed: Uint128,
+    pub prize: Uint128,
+    pub resolved: bool,
+    pub discount: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CurrentRoundResponse {
+    pub open_round: PredictionInfo,
+    pub locked_round: Option<PredictionInfo>,
+    pub seconds_until_lock: u64,
+    pub betting_open: bool,
+    pub up: Uint128,
+    pub down: Uint128,
+    pub flat: Uint128,
+    pub total: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SolvencyResponse {
+    pub balance: Uint128,
+    pub liabilities: Uint128,
+    pub solvent: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HouseResponse {
+    pub balance: Uint128,
+    pub in_play: Uint128,
+    pub total_deposited: Uint128,
+    pub total_withdrawn: Uint128,
+    pub total_won: Uint128,
+    pub total_lost: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JackpotWinResponse {
+    pub address: String,
+    pub amount: Uint128,
+    pub streak: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JackpotResponse {
+    pub balance: Uint128,
+    pub share: Decimal,
+    pub streak: u32,
+    pub min_bet: Uint128,
+    pub total_paid: Uint128,
+    pub winners: Vec<JackpotWinResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StreakResponse {
+    pub address: String,
+    pub streak: u32,
+    pub last_round: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AccruedFeesResponse {
+    pub accrued_fees: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TimeframeResponse {
+    pub timeframe: u32,
+    pub round_time: u64,
+    pub limit_time: u64,
+    pub open_round: u64,
+    pub locked_round: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TimeframesResponse {
+    pub timeframes: Vec<TimeframeResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SubscriptionResponse {
+    pub address: String,
+    pub timeframe: u32,
+    pub side: Outcome,
+    pub amount_per_round: Uint128,
+    pub rounds_left: u64,
+    /// Unspent escrow refunded on cancel
+    pub balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TournamentResponse {
+    pub tournament_id: u64,
+    pub timeframe: u32,
+    pub start_round: u64,
+    pub end_round: u64,
+    pub entry_fee: Uint128,
+    pub starting_chips: Uint128,
+    pub winners: u32,
+    pub prize_pool: Uint128,
+    pub entrants: u32,
+    pub settled: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TournamentEntryResponse {
+    pub tournament_id: u64,
+    pub address: String,
+    /// Chips not in play, expired rounds included
+    pub chips: Uint128,
+    pub bets: Vec<TournamentBet>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BalanceResponse {
+    pub address: String,
+    pub balance: Uint128,
+    pub auto_compound: bool,
+    pub timeframe: Option<u32>,
+    pub side: Option<Outcome>,
 }
@@ -1,38 +1,258 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{CanonicalAddr, Decimal, Uint128};
+use cosmwasm_std::{Binary, CanonicalAddr, Decimal, Uint128};
 use cw_storage_plus::{Item, Map};
 
+use crate::msg::{DiscountTier, FeeModel, FeeSettings, Outcome, TournamentBet};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeRecipient {
+    pub address: CanonicalAddr,
+    pub weight: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
+    pub admin: CanonicalAddr,
     pub pool_address: CanonicalAddr,
     pub collector_address: CanonicalAddr,
+    /// Durations of the default timeframe
     pub round_time: u64,
     pub limit_time: u64,
     pub denom: String,
     pub collector_fee: Decimal,
+    pub fee_model: FeeModel,
+    pub min_fee: Uint128,
+    /// Weighted split of the accrued fees, the collector receives all if empty
+    pub fee_recipients: Vec<FeeRecipient>,
+    /// LOTA staking contract queried for fee discounts
+    pub staking_address: Option<CanonicalAddr>,
+    pub discount_tiers: Vec<DiscountTier>,
+    /// Share of the winnings fee of referred users credited to their referrer, winnings model only
+    pub referral_share: Decimal,
+    /// Amount the house seeds on the empty side of a one-sided round, zero disables it
+    pub house_seed: Uint128,
+    /// Relative band around the locked price resolving flat, zero disables the outcome
+    pub flat_band: Decimal,
+    /// Symmetric bands around the locked price splitting the bucket market, empty disables it
+    pub bucket_bands: Vec<Decimal>,
+    /// Multiplier quoted by the house on fixed odds bets, zero disables them
+    pub fixed_multiplier: Decimal,
+    /// Seconds before the closing time from which bets are refused
+    pub lock_buffer: u64,
+    /// Seconds before the lock buffer reserved to reveal commitments, no bets are taken in it,
+    /// zero disables commit reveal
+    pub reveal_window: u64,
+    /// Share of an unrevealed commitment kept as a fee
+    pub reveal_penalty: Decimal,
+    /// Contracts allowed to bet on behalf of their users
+    pub integrators: Vec<CanonicalAddr>,
+    /// Share of the fees and reveal penalties set aside for the jackpot
+    pub jackpot_share: Decimal,
+    /// Consecutive rounds won needed to claim the jackpot, zero disables claims
+    pub jackpot_streak: u32,
+    /// Stake a round needs to count toward the winning streak
+    pub jackpot_min_bet: Uint128,
+    /// Default timeframe rounds lasting each leaderboard epoch, epochs are consecutive fixed windows
+    pub epoch_rounds: u64,
+    /// Closing time of the first round, epochs are counted from it
+    pub epoch_start: u64,
+    pub leaderboard_min_rounds: u64,
+    /// Keeps the bounded subscription slots from being filled with dust
+    pub min_subscription_bet: Uint128,
 }
 pub const CONFIG: Item<Config> = Item::new("config");
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
+    /// Last round opened, round ids are shared by all timeframes
     pub round: u64,
+    /// Rounding leftover of fully resolved rounds, swept by the admin
+    pub dust: Uint128,
+    /// Open pools, unclaimed winnings, refunds and dust owed by the contract
+    pub liabilities: Uint128,
+    /// Fees collected and waiting to be distributed
+    pub accrued_fees: Uint128,
 }
 
 pub const STATE: Item<State> = Item::new("state");
 
+/// Independent series of rounds with its own durations
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Timeframe {
+    pub round_time: u64,
+    pub limit_time: u64,
+    /// Round accepting bets
+    pub open_round: u64,
+    /// Round waiting for its resolved price
+    pub locked_round: Option<u64>,
+}
+
+pub const TIMEFRAMES: Map<&[u8], Timeframe> = Map::new("timeframes");
+
+/// Bet placed from an escrow in every new round of a timeframe
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Subscription {
+    pub side: Outcome,
+    pub amount_per_round: Uint128,
+    pub rounds_left: u64,
+}
+
+/// Subscriptions by timeframe and address
+pub const SUBSCRIPTIONS: Map<(&[u8], &[u8]), Subscription> = Map::new("subscriptions");
+
+/// Competition over a range of rounds of a timeframe played with virtual chips
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Tournament {
+    pub timeframe: u32,
+    pub start_round: u64,
+    pub end_round: u64,
+    pub entry_fee: Uint128,
+    /// Chips given to each entrant
+    pub starting_chips: Uint128,
+    /// Number of top entrants sharing the prize pool
+    pub winners: u32,
+    /// Entry fees collected, counted in the liabilities until settled
+    pub prize_pool: Uint128,
+    pub entrants: u32,
+    pub settled: bool,
+}
+
+pub const TOURNAMENTS: Map<&[u8], Tournament> = Map::new("tournaments");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TournamentEntry {
+    /// Chips not in play
+    pub chips: Uint128,
+    /// Bets on rounds not expired yet
+    pub bets: Vec<TournamentBet>,
+}
+
+/// Entries by tournament and address
+pub const TOURNAMENT_ENTRIES: Map<(&[u8], &[u8]), TournamentEntry> = Map::new("tournament_entries");
+
+/// Opt-in to bet claims again in the open round of a timeframe instead of receiving them
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AutoCompound {
+    pub timeframe: u32,
+    /// Side to bet, the side of the last claimed game when missing
+    pub side: Option<Outcome>,
+    pub last_side: Option<Outcome>,
+}
+
+pub const AUTO_COMPOUNDS: Map<&[u8], AutoCompound> = Map::new("auto_compounds");
+
+/// Funds held for an address, counted in the liabilities, claims are credited while it exists
+/// and a withdrawal emptying it removes it
+pub const BALANCES: Map<&[u8], Uint128> = Map::new("balances");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Stats {
+    pub total_volume: Uint128,
+    pub total_fees: Uint128,
+    pub total_paid_out: Uint128,
+    pub total_refunded: Uint128,
+    pub unique_players: u64,
+    /// Rounds where the side or the bucket market paid winners
+    pub rounds_succeeded: u64,
+    pub rounds_voided: u64,
+}
+
+pub const STATS: Item<Stats> = Item::new("stats");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct House {
+    /// Liquidity available for seeding or withdrawal
+    pub balance: Uint128,
+    /// Seeds placed in rounds not resolved yet
+    pub in_play: Uint128,
+    pub total_deposited: Uint128,
+    pub total_withdrawn: Uint128,
+    pub total_won: Uint128,
+    pub total_lost: Uint128,
+}
+
+pub const HOUSE: Item<House> = Item::new("house");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JackpotWin {
+    pub address: CanonicalAddr,
+    pub amount: Uint128,
+    pub streak: u32,
+}
+
+/// Side pot funded by fees, counted in the liabilities
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Jackpot {
+    pub balance: Uint128,
+    pub total_paid: Uint128,
+    /// Latest claims first
+    pub winners: Vec<JackpotWin>,
+}
+
+pub const JACKPOT: Item<Jackpot> = Item::new("jackpot");
+
+/// Rounds won in a row by an address, any other round with an outcome in between breaks the streak
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Streak {
+    pub count: u32,
+    pub last_round: u64,
+}
+
+pub const STREAKS: Map<&[u8], Streak> = Map::new("streaks");
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Prediction {
+    /// Timeframe the round belongs to
+    pub timeframe: u32,
     pub up: Uint128,
     pub down: Uint128,
+    pub flat: Uint128,
+    /// Flat band fixed when the round is opened
+    pub flat_band: Decimal,
+    /// Bucket bands fixed when the round is opened
+    pub bucket_bands: Vec<Decimal>,
+    /// Pools of the bucket market, from the lowest prices to the highest
+    pub buckets: Vec<Uint128>,
+    /// Winning bucket once the bucket market is resolved
+    pub bucket: Option<u32>,
+    /// Fee taken from the bucket pools at resolution
+    pub bucket_fee: Uint128,
+    /// Multiplier quoted on fixed odds bets, fixed when the round is opened
+    pub fixed_multiplier: Decimal,
+    /// Fixed odds stakes, kept out of the pools
+    pub fixed_up: Uint128,
+    pub fixed_down: Uint128,
+    /// House liquidity reserved to pay the fixed odds winners of each side
+    pub fixed_reserved_up: Uint128,
+    pub fixed_reserved_down: Uint128,
+    /// Outcome paid to fixed odds bets, none refunds them
+    pub fixed_outcome: Option<Outcome>,
+    /// Commit reveal settings fixed when the round is opened
+    pub reveal_window: u64,
+    pub reveal_penalty: Decimal,
+    /// Commitments not revealed yet, kept out of the pools
+    pub committed: Uint128,
     pub locked_price: Uint128,
     pub resolved_price: Uint128,
     pub closing_time: u64,
     pub expire_time: u64,
     pub success: bool,
     pub is_up: Option<bool>,
-    pub oracle_price_worker: Option<CanonicalAddr>
+    pub is_flat: bool,
+    pub oracle_price_worker: Option<CanonicalAddr>,
+    pub pending_games: u64,
+    /// Payouts, fees and refunds taken from the pool so far
+    pub claimed: Uint128,
+    /// Fee settings fixed when the round is opened rather than locked, the entry fee is charged
+    /// on every bet so all the bets of a round must see the same settings from the first one
+    pub fee_settings: Option<FeeSettings>,
+    /// Fee taken from the pool at resolution
+    pub fee: Uint128,
+    /// House seeds included in the pools
+    pub house_up: Uint128,
+    pub house_down: Uint128,
 }
 
 pub const PREDICTIONS: Map<&[u8], Prediction> = Map::new("predictions");
@@ -41,8 +261,59 @@ pub const PREDICTIONS: Map<&[u8], Prediction> = Map::new("predictions");
 pub struct Game {
     pub up: Uint128,
     pub down: Uint128,
+    pub flat: Uint128,
+    pub buckets: Vec<Uint128>,
+    pub fixed_up: Uint128,
+    pub fixed_down: Uint128,
+    /// Multiplier quoted on the fixed odds stakes
+    pub multiplier: Decimal,
+    /// Hash of the hidden side and the stake waiting for its reveal
+    pub commitment: Option<Binary>,
+    pub committed: Uint128,
     pub prize: Uint128,
     pub resolved: bool,
+    /// Discount on the winnings fee, the lowest tier held when placing the bets
+    pub discount: Decimal,
+    /// Resolved without an outcome, every stake was given back
+    pub voided: bool,
 }
 
 pub const GAMES: Map<(&[u8], &[u8]), Game> = Map::new("games");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct UserStats {
+    pub rounds_played: u64,
+    pub rounds_won: u64,
+    pub rounds_refunded: u64,
+    pub volume: Uint128,
+    pub payout: Uint128,
+}
+
+/// All-time aggregates per user
+pub const USERS: Map<&[u8], UserStats> = Map::new("users");
+/// Aggregates per user and epoch
+pub const USER_EPOCHS: Map<(&[u8], &[u8]), UserStats> = Map::new("user_epochs");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ReferralStats {
+    pub referred_users: u64,
+    pub total_rewards: Uint128,
+    pub pending_rewards: Uint128,
+}
+
+/// Referrer of each user, set once
+pub const REFERRERS: Map<&[u8], CanonicalAddr> = Map::new("referrers");
+/// Rewards and referred users per referrer
+pub const REFERRALS: Map<&[u8], ReferralStats> = Map::new("referrals");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LeaderboardEntry {
+    pub address: CanonicalAddr,
+    pub stats: UserStats,
+}
+
+/// All-time leaderboards keyed by kind
+pub const LEADERBOARDS: Map<&[u8], Vec<LeaderboardEntry>> = Map::new("leaderboards");
+/// Epoch leaderboards keyed by kind and epoch
+pub const EPOCH_LEADERBOARDS: Map<(&[u8], &[u8]), Vec<LeaderboardEntry>> =
+    Map::new("epoch_leaderboards");
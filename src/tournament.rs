@@ -0,0 +1,57 @@
+use cosmwasm_std::{CanonicalAddr, StdResult, Storage, Uint128};
+
+use crate::msg::Outcome;
+use crate::state::{TournamentEntry, PREDICTIONS};
+
+pub const MAX_TOURNAMENT_ROUNDS: u64 = 100;
+pub const MAX_TOURNAMENT_ENTRANTS: u32 = 100;
+
+/// Settle the virtual bets of expired rounds at even money, void and flat rounds give the
+/// chips back as neither side can be bet on a flat outcome
+pub fn settle_entry(storage: &dyn Storage, entry: &mut TournamentEntry, now: u64) -> StdResult<()> {
+    let mut pending = vec![];
+    for bet in entry.bets.drain(..) {
+        let prediction = PREDICTIONS.load(storage, &bet.round.to_be_bytes())?;
+        if prediction.expire_time > now {
+            pending.push(bet);
+            continue;
+        }
+        let returned = match prediction.fixed_outcome {
+            None | Some(Outcome::Flat) => bet.chips,
+            Some(Outcome::Up) if bet.up => bet.chips.checked_add(bet.chips)?,
+            Some(Outcome::Down) if !bet.up => bet.chips.checked_add(bet.chips)?,
+            Some(Outcome::Up) | Some(Outcome::Down) => Uint128::zero(),
+        };
+        entry.chips = entry.chips.checked_add(returned)?;
+    }
+    entry.bets = pending;
+    Ok(())
+}
+
+/// Split the prize pool evenly between the top entrants by chips, ties go to the lowest address
+/// and the first place keeps the rounding
+pub fn prize_shares(
+    mut standings: Vec<(CanonicalAddr, Uint128)>,
+    winners: u32,
+    prize_pool: Uint128,
+) -> StdResult<Vec<(CanonicalAddr, Uint128)>> {
+    standings.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.as_slice().cmp(b.0.as_slice())));
+    standings.truncate(winners as usize);
+    if standings.is_empty() {
+        return Ok(standings);
+    }
+    let share = prize_pool.multiply_ratio(1u128, standings.len() as u128);
+    let rounding =
+        prize_pool.checked_sub(share.checked_mul(Uint128::from(standings.len() as u128))?)?;
+    Ok(standings
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (address, _))| {
+            if rank == 0 {
+                (address, share + rounding)
+            } else {
+                (address, share)
+            }
+        })
+        .collect())
+}